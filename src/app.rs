@@ -1,4 +1,10 @@
-use crossterm::event::{poll, Event, KeyCode, KeyEvent, KeyEventKind};
+use crossterm::{
+    event::{
+        poll, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEvent, KeyEventKind,
+        KeyModifiers, MouseButton, MouseEventKind,
+    },
+    execute,
+};
 
 use ratatui::{
     buffer::Buffer,
@@ -8,36 +14,75 @@ use ratatui::{
     widgets::{Clear, Paragraph, Widget},
     DefaultTerminal, Frame,
 };
-use std::{io, time::Duration};
+use std::{
+    io,
+    time::{Duration, Instant},
+};
 
 use std::cell::RefCell;
 
 use crate::{
-    git::{Git, PushMode},
+    config::get_key_name,
+    git::{Git, HeadState, Journal, PushMode},
+    keymap::{find_action, get_actions, get_page_keys, Action, GLOBAL_KEYS},
     pages::Pages,
-    tabs::{BranchTab, StatusTab},
+    palette::Palette,
+    popup::Help,
+    tabs::{BranchTab, ConfigTab, LogTab, Move, ReflogTab, StashTab, StatusTab},
 };
 
 pub struct App {
     pub exit: bool,
     pub page: Pages,
+    pub mouse: bool,
     pub status_page: RefCell<StatusTab>,
     pub branch_page: BranchTab,
+    pub log_page: LogTab,
+    pub reflog_page: ReflogTab,
+    pub config_page: ConfigTab,
+    pub stash_page: StashTab,
+    pub help: Help,
+    pub palette: Palette,
+    /// time and cell of the last left click, to detect double clicks
+    pub last_click: Option<(Instant, u16, u16)>,
+    /// an action may have changed the files since the Status lists were read
+    pub status_outdated: bool,
     pub git: Git,
 }
 
-const PAGESNAME: [&str; 3] = [" [1 status] ", " [2 Branch] ", " [3 Config] "];
+const PAGESNAME: [&str; 6] = [
+    " [1 status] ",
+    " [2 Branch] ",
+    " [3 Log] ",
+    " [4 Reflog] ",
+    " [5 Config] ",
+    " [6 Stash] ",
+];
+
+/// longest time between the two clicks of a double click
+const DOUBLE_CLICK_DELAY: Duration = Duration::from_millis(400);
+
+/// time between two reads of the files of the Status lists when nothing happens, for the
+/// changes made outside git-ui
+const STATUS_REFRESH_DELAY: Duration = Duration::from_secs(2);
 
 impl App {
     /// runs the application's main loop until the user quits
     pub fn run(&mut self, terminal: &mut DefaultTerminal) -> io::Result<()> {
+        if self.mouse {
+            execute!(io::stdout(), EnableMouseCapture)?;
+        }
         while !self.exit {
-            if self.git.push_process && self.page == Pages::StatusPAGE {
-                self.git.update_push_status();
+            // a remote branch deleted from the Branch page shows its progress there too
+            if self.git.push_process && self.git.update_push_status() {
+                self.branch_page.reset_branch(&mut self.git);
             }
             self.handle_events()?;
             terminal.draw(|frame| self.draw(frame))?;
         }
+        if self.mouse {
+            execute!(io::stdout(), DisableMouseCapture)?;
+        }
         Ok(())
     }
 
@@ -68,26 +113,128 @@ impl App {
                         .newbranch_popup
                         .draw_popup(frame, content, "New branch");
                 }
+                if self.git.push_mode == PushMode::DeleteRemote {
+                    self.git.draw_push(frame, content);
+                }
             }
-            _ => {}
+            Pages::LogPAGE => self.log_page.draw(frame, content, &self.git.config),
+            Pages::ReflogPAGE => self.reflog_page.draw(frame, content, &self.git),
+            Pages::ConfigPage => self.config_page.draw(frame, content),
+            Pages::StashPAGE => self.stash_page.draw(frame, content, &self.git),
+        }
+        if self.git.commit_popup.activated && self.page != Pages::StatusPAGE {
+            self.git.commit_popup.draw_popup(frame, content, "Commit");
+        }
+        if self.git.notification.activated {
+            self.git.notification.draw_popup(frame, content);
+        }
+        if self.palette.activated {
+            self.palette.draw_popup(frame, content, &self.git.config);
+        }
+        if self.help.activated {
+            self.help.draw_popup(frame, content);
         }
         frame.render_widget(self, frame.area());
     }
 
     fn handle_events(&mut self) -> io::Result<()> {
-        if poll(Duration::from_millis(100))? {
+        if poll(Duration::from_millis(self.git.config.refresh_interval))? {
+            let popup_was_open = self.is_popup_open();
             match crossterm::event::read()? {
                 Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
                     self.handle_key_event(key_event)
                 }
+                // the wheel moves like the arrow keys
+                Event::Mouse(mouse_event) => match mouse_event.kind {
+                    MouseEventKind::ScrollUp => self.handle_key_event(KeyEvent::from(KeyCode::Up)),
+                    MouseEventKind::ScrollDown => {
+                        self.handle_key_event(KeyEvent::from(KeyCode::Down))
+                    }
+                    MouseEventKind::Down(MouseButton::Left) => {
+                        self.handle_click(mouse_event.column, mouse_event.row)?
+                    }
+                    _ => {}
+                },
                 _ => {}
             };
+            // an action ran, or a popup closed after its own
+            let popup_closed = popup_was_open && !self.is_popup_open();
+            if self.page == Pages::StatusPAGE && (self.status_outdated || popup_closed) {
+                self.refresh_status();
+            }
+        } else if self.page == Pages::StatusPAGE
+            && self.git.status_details.loaded_at.elapsed() >= STATUS_REFRESH_DELAY
+        {
+            self.refresh_status();
         }
         Ok(())
     }
 
+    /// read again the files of the Status lists and their details
+    fn refresh_status(&mut self) {
+        self.status_outdated = false;
+        self.git
+            .refresh_status_details(self.status_page.borrow().list_options);
+    }
+
+    /// switch page from the header, select a file; a second click on it stages / unstages it
+    fn handle_click(&mut self, column: u16, row: u16) -> io::Result<()> {
+        let double_click = self
+            .last_click
+            .is_some_and(|(time, last_column, last_row)| {
+                time.elapsed() < DOUBLE_CLICK_DELAY && (last_column, last_row) == (column, row)
+            });
+        self.last_click = Some((Instant::now(), column, row));
+        if self.is_popup_open() {
+            return Ok(());
+        }
+        if row == 0 {
+            let (width, _height) = crossterm::terminal::size()?;
+            if let Some(page) = get_clicked_page(column, width) {
+                self.open_page(page);
+            }
+            return Ok(());
+        }
+        if self.page != Pages::StatusPAGE {
+            return Ok(());
+        }
+        let file_clicked = self.status_page.borrow_mut().click(column, row);
+        if file_clicked && double_click {
+            let name = match self.get_block_name() {
+                Some("staged") => "restore",
+                _ => "add",
+            };
+            self.last_click = None;
+            if let Some(action) = find_action(&Pages::StatusPAGE, name) {
+                self.run_page_action(action);
+            }
+        }
+        Ok(())
+    }
+
+    /// a popup, the help or the palette waits for the keyboard
+    fn is_popup_open(&self) -> bool {
+        self.git.notification.activated
+            || self.help.activated
+            || self.palette.activated
+            || self.git.commit_popup.activated
+            || self.branch_page.newbranch_popup.activated
+            || self.branch_page.name_popup.activated
+            || self.status_page.borrow().filter_popup.activated
+            || self.config_page.edit_popup.activated
+            || self.get_popup_help().is_some()
+    }
+
     fn handle_key_event(&mut self, key_event: KeyEvent) {
-        if self.git.push_mode == PushMode::Push {
+        if self.git.notification.activated {
+            self.git.notification.activated = false;
+            return;
+        }
+        if self.help.activated {
+            self.help.activated = false;
+            return;
+        }
+        if self.git.push_mode != PushMode::Normal {
             self.git.push_key_event(key_event);
             return;
         }
@@ -100,36 +247,336 @@ impl App {
                 .newbranch_key_event(key_event, &mut self.git);
             return;
         }
+        if self.branch_page.name_popup.activated {
+            self.branch_page.name_key_event(key_event, &mut self.git);
+            return;
+        }
+        if self.page == Pages::StatusPAGE && self.status_page.borrow().filter_popup.activated {
+            self.status_page.borrow_mut().filter_key_event(key_event);
+            return;
+        }
+        if self.config_page.edit_popup.activated {
+            self.config_page.edit_key_event(key_event, &mut self.git);
+            return;
+        }
+        if self.palette.activated {
+            if let Some((page, action)) = self.palette.handle_key_event(key_event) {
+                self.run_action(page, action);
+            }
+            return;
+        }
+        // every popup still open only waits for a key, `?` explains which
+        if key_event.code == KeyCode::Char('?') {
+            self.show_help();
+            return;
+        }
+        if self.status_page.borrow().ignore_popup.is_activated() {
+            self.status_page
+                .borrow_mut()
+                .ignore_key_event(key_event, &mut self.git);
+            return;
+        }
+        if self.status_page.borrow().confirm_popup.is_activated() {
+            self.status_page
+                .borrow_mut()
+                .confirm_key_event(key_event, &mut self.git);
+            return;
+        }
+        if self.branch_page.confirm_popup.is_activated() {
+            self.branch_page.confirm_key_event(key_event, &mut self.git);
+            return;
+        }
+        if self.branch_page.checkout_popup.is_activated() {
+            self.branch_page
+                .checkout_key_event(key_event, &mut self.git);
+            return;
+        }
+        if self.log_page.confirm_popup.is_activated() {
+            self.log_page.confirm_key_event(key_event, &mut self.git);
+            return;
+        }
+        if self.reflog_page.confirm_popup.is_activated() {
+            self.reflog_page.confirm_key_event(key_event, &mut self.git);
+            return;
+        }
+        if self.stash_page.confirm_popup.is_activated() {
+            self.stash_page.confirm_key_event(key_event, &mut self.git);
+            return;
+        }
+        if self.config_page.level_popup.is_activated() {
+            self.config_page.level_key_event(key_event);
+            return;
+        }
+        if self.config_page.confirm_popup.is_activated() {
+            self.config_page.confirm_key_event(key_event, &mut self.git);
+            return;
+        }
+        let ctrl_p =
+            key_event.modifiers == KeyModifiers::CONTROL && key_event.code == KeyCode::Char('p');
+        if key_event.code == KeyCode::Char(':') || ctrl_p {
+            self.palette.open(self.page);
+            return;
+        }
+        if key_event.modifiers == KeyModifiers::CONTROL && key_event.code == KeyCode::Char('z') {
+            if !self.git.refuse_if_readonly() {
+                self.undo();
+            }
+            return;
+        }
+        // the actions of the page, with the keys of the configuration.
+        // Modified keys only move (Ctrl+direction, Alt+letter, Shift+Up/Down)
+        let modified = key_event
+            .modifiers
+            .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT);
+        let block = self.get_block_name();
+        let action = self
+            .git
+            .config
+            .get_action(&self.page, block, key_event.code);
+        if !modified && let Some(action) = action {
+            self.run_page_action(action);
+            return;
+        }
+        self.dispatch_key_event(key_event);
+        // Alt+q or Ctrl+1 belong to the page, they neither quit nor switch pages
+        if modified {
+            return;
+        }
+        match key_event.code {
+            KeyCode::Char('q') | KeyCode::Esc => self.exit(),
+            KeyCode::Char(char @ '1'..='6') => {
+                let nb: u32 = char.to_digit(10).unwrap();
+                let page = self.page.change_page(nb - 1);
+                self.open_page(page);
+            }
+            _ => {}
+        }
+    }
+
+    /// give a key that runs no action to the page shown, to move in it
+    fn dispatch_key_event(&mut self, key_event: KeyEvent) {
         match self.page {
             Pages::StatusPAGE => self
                 .status_page
                 .borrow_mut()
                 .handle_key_event(key_event, &mut self.git),
-            Pages::BranchPAGE => {
-                self.branch_page.handle_key_event(key_event, &mut self.git);
+            Pages::BranchPAGE => self.branch_page.handle_key_event(key_event, &mut self.git),
+            Pages::LogPAGE => self.log_page.handle_key_event(key_event),
+            Pages::ReflogPAGE => self.reflog_page.handle_key_event(key_event, &mut self.git),
+            Pages::ConfigPage => self.config_page.handle_key_event(key_event),
+            Pages::StashPAGE => self.stash_page.handle_key_event(key_event, &mut self.git),
+        }
+    }
+
+    /// run `action` of the page shown on its selection, unless it changes a read-only repository
+    fn run_page_action(&mut self, action: &Action) {
+        if action.mutating {
+            if self.git.refuse_if_readonly() {
+                return;
             }
-            Pages::ConfigPage => {}
+            self.status_outdated = true;
         }
-        match key_event.code {
-            KeyCode::Char('q') | KeyCode::Esc => self.exit(),
-            KeyCode::Char(char @ '1'..='3') => {
-                let nb: u32 = char.to_digit(10).unwrap();
-                self.page = self.page.change_page(nb - 1);
-                if self.page == Pages::BranchPAGE {
-                    self.branch_page.nb_local_branch = self.git.branch.local_branches.len() as u16;
-                    self.branch_page.nb_remote_branch =
-                        self.git.branch.remote_branches.len() as u16;
+        match self.page {
+            Pages::StatusPAGE => self
+                .status_page
+                .borrow_mut()
+                .run_action(action.name, &mut self.git),
+            Pages::BranchPAGE => {
+                self.branch_page.run_action(action.name, &mut self.git);
+                if let Some(reference) = self.branch_page.open_log.take() {
+                    self.page = Pages::LogPAGE;
+                    self.log_page.open(&reference, &mut self.git);
                 }
             }
+            Pages::LogPAGE => self.log_page.run_action(action.name, &mut self.git),
+            Pages::ReflogPAGE => self.reflog_page.run_action(action.name, &mut self.git),
+            Pages::ConfigPage => self.config_page.run_action(action.name),
+            Pages::StashPAGE => self.stash_page.run_action(action.name, &mut self.git),
+        }
+    }
+
+    /// run an action picked in the palette on the selection of its page,
+    /// focusing a block where it works first
+    fn run_action(&mut self, page: Pages, action: &Action) {
+        if self.page != page {
+            self.open_page(page);
+        }
+        if !action.is_available(self.get_block_name())
+            && let Some(block) = action.blocks.first()
+        {
+            match self.page {
+                Pages::StatusPAGE => self.status_page.borrow_mut().focus_block(block),
+                Pages::BranchPAGE => self.branch_page.focus_block(block),
+                Pages::LogPAGE => self.log_page.focus_block(block),
+                Pages::ReflogPAGE => self.reflog_page.focus_block(block),
+                Pages::ConfigPage => self.config_page.focus_block(block),
+                Pages::StashPAGE => self.stash_page.focus_block(block),
+            }
+        }
+        self.run_page_action(action);
+    }
+
+    /// switch to `page` and load what it shows
+    pub fn open_page(&mut self, page: Pages) {
+        self.page = page;
+        // branches and their ahead / behind counts may have moved since (log page, other tools)
+        if self.page == Pages::StatusPAGE || self.page == Pages::BranchPAGE {
+            self.branch_page.reset_branch(&mut self.git);
+        }
+        if self.page == Pages::StatusPAGE {
+            self.refresh_status();
+        }
+        if self.page == Pages::LogPAGE {
+            self.log_page.refresh(&mut self.git);
+        }
+        if self.page == Pages::ReflogPAGE {
+            self.reflog_page.refresh(&mut self.git);
+        }
+        if self.page == Pages::ConfigPage {
+            self.config_page.refresh(&mut self.git);
+        }
+        if self.page == Pages::StashPAGE {
+            self.stash_page.refresh(&mut self.git);
+        }
+    }
+
+    fn get_block_name(&self) -> Option<&'static str> {
+        match self.page {
+            Pages::StatusPAGE => self.status_page.borrow().get_block_name(),
+            Pages::BranchPAGE => self.branch_page.get_block_name(),
+            Pages::LogPAGE => self.log_page.get_block_name(),
+            Pages::ReflogPAGE => self.reflog_page.get_block_name(),
+            Pages::ConfigPage => self.config_page.get_block_name(),
+            Pages::StashPAGE => self.stash_page.get_block_name(),
+        }
+    }
+
+    /// keys of the open popup, None when no popup waits for an answer
+    fn get_popup_help(&self) -> Option<Vec<(String, String)>> {
+        if self.git.push_mode == PushMode::Push {
+            return Some(vec![
+                ("Enter".to_string(), "push".to_string()),
+                ("Esc".to_string(), "cancel".to_string()),
+            ]);
+        }
+        if self.git.push_mode == PushMode::DeleteRemote {
+            return Some(vec![("Esc / Enter".to_string(), "close".to_string())]);
+        }
+        let status_page = self.status_page.borrow();
+        [
+            status_page.confirm_popup.get_help(),
+            status_page.ignore_popup.get_help(),
+            self.branch_page.confirm_popup.get_help(),
+            self.branch_page.checkout_popup.get_help(),
+            self.log_page.confirm_popup.get_help(),
+            self.reflog_page.confirm_popup.get_help(),
+            self.config_page.level_popup.get_help(),
+            self.config_page.confirm_popup.get_help(),
+            self.stash_page.confirm_popup.get_help(),
+        ]
+        .into_iter()
+        .flatten()
+        .next()
+    }
+
+    /// actions of the focused block with the keys they are bound to, then the global keys
+    fn show_help(&mut self) {
+        let block = self.get_block_name();
+        let mut title = format!("Help: {}", self.page.get_name());
+        if let Some(block) = block {
+            title.push_str(&format!(" ({})", block));
+        }
+
+        let mut sections = Vec::new();
+        if let Some(keys) = self.get_popup_help() {
+            sections.push(("Popup".to_string(), keys));
+        }
+        let mut actions: Vec<(String, String)> = get_actions(&self.page)
+            .iter()
+            .filter(|action| action.is_available(block))
+            .map(|action| {
+                let key = self.git.config.get_key(&self.page, action);
+                (get_key_name(key), action.description.to_string())
+            })
+            .collect();
+        actions.extend(
+            get_page_keys(&self.page)
+                .iter()
+                .map(|(key, description)| (key.to_string(), description.to_string())),
+        );
+        if !actions.is_empty() {
+            sections.push((self.page.get_name().to_string(), actions));
+        }
+        sections.push((
+            "Global".to_string(),
+            GLOBAL_KEYS
+                .iter()
+                .map(|(key, description)| (key.to_string(), description.to_string()))
+                .collect(),
+        ));
+        self.help.show(&title, sections);
+    }
+
+    /// revert the last move of HEAD or the last journaled branch operation and reload
+    /// what depends on it
+    fn undo(&mut self) {
+        self.status_outdated = true;
+        match self.git.undo_last() {
+            Ok(description) => self
+                .git
+                .notification
+                .show("Undo", vec![format!("Undone: {}", description)]),
+            Err(e) => self
+                .git
+                .notification
+                .show("Undo", vec![e.message().to_string()]),
+        }
+        self.branch_page.reset_branch(&mut self.git);
+        match self.page {
+            Pages::LogPAGE => self.log_page.refresh(&mut self.git),
+            Pages::ReflogPAGE => self.reflog_page.refresh(&mut self.git),
             _ => {}
         }
     }
 
+    /// "main → origin/main ↑1 ↓2" under the page names of the Status page
+    fn get_branch_summary(&self) -> Line<'static> {
+        let branch = &self.git.branch;
+        let head = match branch.head {
+            HeadState::OnBranch => branch.current.clone(),
+            HeadState::Detached => format!("HEAD detached at {}", branch.current),
+            HeadState::Unborn => format!("{} (no commit yet)", branch.current),
+        };
+        let mut spans = vec![Span::raw(head)];
+        if let Some(info) = branch.get_current_info()
+            && let Some(tracking) = info.get_tracking_text()
+        {
+            let color = if info.gone { Color::Red } else { Color::Cyan };
+            spans.push(Span::raw(" → "));
+            spans.push(Span::styled(tracking, Style::default().fg(color)));
+        }
+        Line::from(spans)
+    }
+
     fn exit(&mut self) {
         self.exit = true
     }
 }
 
+/// page whose name is at `column` in the header centered on `width` columns
+fn get_clicked_page(column: u16, width: u16) -> Option<Pages> {
+    let header_width: usize = PAGESNAME.iter().map(|name| name.chars().count()).sum();
+    let mut start = (width as usize).saturating_sub(header_width) / 2;
+    for (i, name) in PAGESNAME.iter().enumerate() {
+        let end = start + name.chars().count();
+        if (start..end).contains(&(column as usize)) {
+            return Some(Pages::StatusPAGE.change_page(i as u32));
+        }
+        start = end;
+    }
+    None
+}
+
 impl Widget for &App {
     fn render(self, area: Rect, buf: &mut Buffer) {
         // Create header area (top 3 lines)
@@ -141,22 +588,22 @@ impl Widget for &App {
         };
 
         // Create header with page navigation
-        let mut pages: [Span; 3] = [
-            Span::raw(PAGESNAME[0]),
-            Span::raw(PAGESNAME[1]),
-            Span::raw(PAGESNAME[2]),
-        ];
-
-        for i in 0..=2 {
-            if i == self.page.to_index() {
-                pages[i] = Span::styled(
-                    PAGESNAME[i],
-                    Style::default().bg(Color::Red).fg(Color::White),
-                );
-            }
-        }
+        let pages: Vec<Span> = PAGESNAME
+            .iter()
+            .enumerate()
+            .map(|(i, name)| {
+                if i == self.page.to_index() {
+                    Span::styled(*name, Style::default().bg(Color::Red).fg(Color::White))
+                } else {
+                    Span::raw(*name)
+                }
+            })
+            .collect();
 
-        let header_text = Line::from(vec![pages[0].clone(), pages[1].clone(), pages[2].clone()]);
+        let mut header_text = vec![Line::from(pages)];
+        if self.page == Pages::StatusPAGE {
+            header_text.push(self.get_branch_summary());
+        }
 
         Paragraph::new(header_text)
             .alignment(Alignment::Center)
@@ -0,0 +1,184 @@
+use std::path::PathBuf;
+
+use crate::pages::Pages;
+
+pub const USAGE: &str = "\
+usage: git-ui [options] [status | branch | log | reflog | config | stash] [<path>]
+
+Terminal UI for git, opened on the repository containing <path> (default: current directory)
+
+options:
+  -C <path>          run as if git-ui was started in <path>
+  --config <file>    read the configuration from <file>
+  --theme <name>     syntax theme of the diff (see `delta --list-syntax-themes`)
+  --no-mouse         leave the mouse to the terminal, for copy/paste
+  --readonly         browse only: every action changing the repository is disabled
+  -h, --help         print this help
+  -V, --version      print the version";
+
+pub struct Cli {
+    pub start_dir: Option<PathBuf>,
+    pub page: Pages,
+    pub config: Option<PathBuf>,
+    pub theme: Option<String>,
+    pub mouse: bool,
+    pub readonly: bool,
+}
+
+pub enum Command {
+    Run(Cli),
+    Help,
+    Version,
+}
+
+pub fn parse_args(args: impl Iterator<Item = String>) -> Result<Command, String> {
+    let mut cli = Cli {
+        start_dir: None,
+        page: Pages::StatusPAGE,
+        config: None,
+        theme: None,
+        mouse: true,
+        readonly: false,
+    };
+    let mut page_given = false;
+    let mut args = args.peekable();
+
+    while let Some(arg) = args.next() {
+        let mut value_of = |option: &str| {
+            args.next()
+                .ok_or_else(|| format!("{} needs a value", option))
+        };
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(Command::Help),
+            "-V" | "--version" => return Ok(Command::Version),
+            "-C" => set_start_dir(&mut cli, value_of("-C")?)?,
+            "--config" => cli.config = Some(PathBuf::from(value_of("--config")?)),
+            "--theme" => cli.theme = Some(value_of("--theme")?),
+            "--no-mouse" => cli.mouse = false,
+            "--readonly" => cli.readonly = true,
+            flag if flag.starts_with('-') => return Err(format!("unknown option {}", flag)),
+            _ => match get_page(&arg) {
+                Some(page) if !page_given => {
+                    cli.page = page;
+                    page_given = true;
+                }
+                _ => set_start_dir(&mut cli, arg)?,
+            },
+        }
+    }
+    Ok(Command::Run(cli))
+}
+
+fn get_page(name: &str) -> Option<Pages> {
+    match name {
+        "status" => Some(Pages::StatusPAGE),
+        "branch" => Some(Pages::BranchPAGE),
+        "log" => Some(Pages::LogPAGE),
+        "reflog" => Some(Pages::ReflogPAGE),
+        "config" => Some(Pages::ConfigPage),
+        "stash" => Some(Pages::StashPAGE),
+        _ => None,
+    }
+}
+
+fn set_start_dir(cli: &mut Cli, path: String) -> Result<(), String> {
+    if cli.start_dir.is_some() {
+        return Err("only one repository path can be given".to_string());
+    }
+    cli.start_dir = Some(PathBuf::from(path));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Command, String> {
+        parse_args(args.iter().map(|arg| arg.to_string()))
+    }
+
+    fn parse_cli(args: &[&str]) -> Cli {
+        match parse(args) {
+            Ok(Command::Run(cli)) => cli,
+            _ => panic!("{:?} should run the app", args),
+        }
+    }
+
+    #[test]
+    fn defaults_without_arguments() {
+        let cli = parse_cli(&[]);
+        assert!(cli.start_dir.is_none());
+        assert!(cli.page == Pages::StatusPAGE);
+        assert!(cli.config.is_none());
+        assert!(cli.theme.is_none());
+        assert!(cli.mouse);
+        assert!(!cli.readonly);
+    }
+
+    #[test]
+    fn help_and_version_win() {
+        assert!(matches!(parse(&["--readonly", "-h"]), Ok(Command::Help)));
+        assert!(matches!(parse(&["--help"]), Ok(Command::Help)));
+        assert!(matches!(parse(&["-V", "log"]), Ok(Command::Version)));
+        assert!(matches!(parse(&["--version"]), Ok(Command::Version)));
+    }
+
+    #[test]
+    fn options_with_values() {
+        let cli = parse_cli(&[
+            "-C",
+            "/tmp/repo",
+            "--config",
+            "git-ui.toml",
+            "--theme",
+            "Nord",
+            "--no-mouse",
+            "--readonly",
+        ]);
+        assert_eq!(cli.start_dir, Some(PathBuf::from("/tmp/repo")));
+        assert_eq!(cli.config, Some(PathBuf::from("git-ui.toml")));
+        assert_eq!(cli.theme.as_deref(), Some("Nord"));
+        assert!(!cli.mouse);
+        assert!(cli.readonly);
+    }
+
+    #[test]
+    fn missing_value_and_unknown_option() {
+        assert_eq!(parse(&["--theme"]).err().unwrap(), "--theme needs a value");
+        assert_eq!(parse(&["-C"]).err().unwrap(), "-C needs a value");
+        assert_eq!(parse(&["--color"]).err().unwrap(), "unknown option --color");
+    }
+
+    #[test]
+    fn first_page_name_then_path() {
+        let cli = parse_cli(&["branch", "../other"]);
+        assert!(cli.page == Pages::BranchPAGE);
+        assert_eq!(cli.start_dir, Some(PathBuf::from("../other")));
+
+        // a second page name is a directory
+        let cli = parse_cli(&["log", "config"]);
+        assert!(cli.page == Pages::LogPAGE);
+        assert_eq!(cli.start_dir, Some(PathBuf::from("config")));
+
+        let cli = parse_cli(&["reflog"]);
+        assert!(cli.page == Pages::ReflogPAGE);
+        assert!(cli.start_dir.is_none());
+    }
+
+    #[test]
+    fn only_one_repository_path() {
+        assert!(parse(&["a", "b"]).is_err());
+        assert!(parse(&["-C", "a", "b"]).is_err());
+    }
+
+    #[test]
+    fn stash_is_a_page() {
+        let cli = parse_cli(&["stash"]);
+        assert!(cli.page == Pages::StashPAGE);
+        assert!(cli.start_dir.is_none());
+
+        let cli = parse_cli(&["stash", "stash"]);
+        assert!(cli.page == Pages::StashPAGE);
+        assert_eq!(cli.start_dir, Some(PathBuf::from("stash")));
+    }
+}
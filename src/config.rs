@@ -0,0 +1,460 @@
+use crossterm::event::KeyCode;
+use ratatui::style::Color;
+use serde::Deserialize;
+use std::{
+    collections::HashMap,
+    env, fs,
+    path::{Path, PathBuf},
+};
+use toml::Table;
+
+use crate::{
+    git::FileStatus,
+    keymap::{find_action, get_actions, Action},
+    pages::Pages,
+};
+
+/// below it the event loop would spin on `poll` for nothing
+const MIN_REFRESH_INTERVAL: u64 = 20;
+
+/// content of config.toml, every missing field keeps its default
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// milliseconds between two refreshes of the screen when no key is pressed
+    pub refresh_interval: u64,
+    /// capture the mouse, false leaves it to the terminal like --no-mouse
+    pub mouse: bool,
+    pub keys: Keys,
+    pub colors: Colors,
+    pub diff: DiffConfig,
+    pub push: PushConfig,
+}
+
+/// action name -> key, by page. See `keymap::get_actions` for the names and default keys
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct Keys {
+    pub status: HashMap<String, String>,
+    pub branch: HashMap<String, String>,
+    pub log: HashMap<String, String>,
+    pub reflog: HashMap<String, String>,
+    pub config: HashMap<String, String>,
+    pub stash: HashMap<String, String>,
+}
+
+/// colors of the file states in the Status lists
+#[derive(Deserialize, Clone, Copy)]
+#[serde(default, deny_unknown_fields)]
+pub struct Colors {
+    pub modified: Color,
+    pub deleted: Color,
+    pub renamed: Color,
+    pub typechange: Color,
+    pub added: Color,
+    pub untracked: Color,
+    pub conflicted: Color,
+    pub ignored: Color,
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum DiffRenderer {
+    /// `git diff` piped into delta
+    Delta,
+    /// colored `git diff` output, for machines without delta
+    Git,
+}
+
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DiffConfig {
+    pub renderer: DiffRenderer,
+    pub delta_args: Vec<String>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct PushConfig {
+    /// remote to push to, instead of guessing it (origin, upstream, then the first one)
+    pub remote: Option<String>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            refresh_interval: 100,
+            mouse: true,
+            keys: Keys::default(),
+            colors: Colors::default(),
+            diff: DiffConfig::default(),
+            push: PushConfig::default(),
+        }
+    }
+}
+
+impl Default for Colors {
+    fn default() -> Self {
+        Colors {
+            modified: Color::Yellow,
+            deleted: Color::Red,
+            renamed: Color::Blue,
+            typechange: Color::Magenta,
+            added: Color::Green,
+            untracked: Color::LightGreen,
+            conflicted: Color::LightRed,
+            ignored: Color::DarkGray,
+        }
+    }
+}
+
+impl Colors {
+    pub fn get_color(&self, status: FileStatus) -> Color {
+        match status {
+            FileStatus::Modified => self.modified,
+            FileStatus::Deleted => self.deleted,
+            FileStatus::Renamed => self.renamed,
+            FileStatus::Typechange => self.typechange,
+            FileStatus::Added => self.added,
+            FileStatus::Untracked => self.untracked,
+            FileStatus::Conflicted => self.conflicted,
+            FileStatus::Ignored => self.ignored,
+        }
+    }
+}
+
+impl Default for DiffConfig {
+    fn default() -> Self {
+        DiffConfig {
+            renderer: DiffRenderer::Delta,
+            delta_args: vec![
+                "--line-numbers".to_string(),
+                "--syntax-theme=GitHub".to_string(),
+            ],
+        }
+    }
+}
+
+impl Config {
+    /// user config ($XDG_CONFIG_HOME/git-ui/config.toml, or `user_file` given on the command line)
+    /// overridden key by key by the repository one (.git/git-ui.toml)
+    pub fn load(user_file: Option<&Path>, git_dir: &Path) -> Result<Config, String> {
+        let mut table = Table::new();
+        let user_file = user_file
+            .map(Path::to_path_buf)
+            .or_else(get_user_config_path);
+        for path in user_file.iter().chain([&git_dir.join("git-ui.toml")]) {
+            if !path.is_file() {
+                continue;
+            }
+            let content = fs::read_to_string(path)
+                .map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+            let file_table: Table = content
+                .parse()
+                .map_err(|e| format!("{}: {}", path.display(), e))?;
+            merge_tables(&mut table, file_table);
+        }
+
+        let config: Config = table
+            .try_into()
+            .map_err(|e| format!("Invalid configuration: {}", e.to_string().trim_end()))?;
+        config.check_refresh_interval()?;
+        config.check_keys()?;
+        Ok(config)
+    }
+
+    /// the delta theme given with --theme wins over the one of the config
+    pub fn set_syntax_theme(&mut self, theme: &str) {
+        self.diff
+            .delta_args
+            .retain(|arg| !arg.starts_with("--syntax-theme"));
+        self.diff
+            .delta_args
+            .push(format!("--syntax-theme={}", theme));
+    }
+
+    fn get_page_keys(&self, page: &Pages) -> &HashMap<String, String> {
+        match page {
+            Pages::StatusPAGE => &self.keys.status,
+            Pages::BranchPAGE => &self.keys.branch,
+            Pages::LogPAGE => &self.keys.log,
+            Pages::ReflogPAGE => &self.keys.reflog,
+            Pages::ConfigPage => &self.keys.config,
+            Pages::StashPAGE => &self.keys.stash,
+        }
+    }
+
+    fn check_refresh_interval(&self) -> Result<(), String> {
+        if self.refresh_interval < MIN_REFRESH_INTERVAL {
+            return Err(format!(
+                "refresh_interval must be at least {} milliseconds, got {}",
+                MIN_REFRESH_INTERVAL, self.refresh_interval
+            ));
+        }
+        Ok(())
+    }
+
+    fn check_keys(&self) -> Result<(), String> {
+        let pages = [
+            ("status", Pages::StatusPAGE),
+            ("branch", Pages::BranchPAGE),
+            ("log", Pages::LogPAGE),
+            ("reflog", Pages::ReflogPAGE),
+            ("config", Pages::ConfigPage),
+            ("stash", Pages::StashPAGE),
+        ];
+        for (page_name, page) in pages {
+            for (action, key) in self.get_page_keys(&page) {
+                if !get_actions(&page).iter().any(|known| known.name == action) {
+                    return Err(format!(
+                        "Unknown action '{}' in [keys.{}]",
+                        action, page_name
+                    ));
+                }
+                match parse_key(key) {
+                    None => return Err(format!("Unknown key '{}' for '{}'", key, action)),
+                    Some(KeyCode::Char(reserved @ ('?' | ':'))) => {
+                        return Err(format!(
+                            "'{}' opens the help or the palette, cannot bind '{}'",
+                            reserved, action
+                        ));
+                    }
+                    Some(KeyCode::Char(reserved @ ('q' | '1'..='6'))) => {
+                        return Err(format!(
+                            "'{}' quits or switches pages, cannot bind '{}'",
+                            reserved, action
+                        ));
+                    }
+                    Some(_) => {}
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// the action of `page` that `code` runs in `block`: the one the configuration binds to it,
+    /// else the one built on it unless the configuration moved it to another key
+    pub fn get_action(
+        &self,
+        page: &Pages,
+        block: Option<&str>,
+        code: KeyCode,
+    ) -> Option<&'static Action> {
+        let keys = self.get_page_keys(page);
+        let mut actions = get_actions(page)
+            .iter()
+            .filter(|action| action.is_available(block));
+        let bound = actions
+            .clone()
+            .find(|action| keys.get(action.name).and_then(|key| parse_key(key)) == Some(code));
+        bound.or_else(|| {
+            actions.find(|action| action.key == code && !keys.contains_key(action.name))
+        })
+    }
+
+    /// key bound to `action` of `page`, by the configuration or built in
+    pub fn get_key(&self, page: &Pages, action: &Action) -> KeyCode {
+        self.get_page_keys(page)
+            .get(action.name)
+            .and_then(|key| parse_key(key))
+            .unwrap_or(action.key)
+    }
+
+    /// name of the key running the action called `name` of `page`, for the hints on screen
+    pub fn get_action_key_name(&self, page: &Pages, name: &str) -> String {
+        find_action(page, name)
+            .map(|action| get_key_name(self.get_key(page, action)))
+            .unwrap_or_default()
+    }
+}
+
+/// "a", "A", "space", "enter", "tab", "backspace", "delete", "home", "end", "pageup", "pagedown"
+pub fn parse_key(key: &str) -> Option<KeyCode> {
+    let mut chars = key.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return Some(KeyCode::Char(c));
+    }
+    match key.to_lowercase().as_str() {
+        "space" => Some(KeyCode::Char(' ')),
+        "enter" => Some(KeyCode::Enter),
+        "tab" => Some(KeyCode::Tab),
+        "backspace" => Some(KeyCode::Backspace),
+        "delete" => Some(KeyCode::Delete),
+        "home" => Some(KeyCode::Home),
+        "end" => Some(KeyCode::End),
+        "pageup" => Some(KeyCode::PageUp),
+        "pagedown" => Some(KeyCode::PageDown),
+        _ => None,
+    }
+}
+
+/// name of `code` as written in the configuration, for the help
+pub fn get_key_name(code: KeyCode) -> String {
+    match code {
+        KeyCode::Char(' ') => "space".to_string(),
+        KeyCode::Char(c) => c.to_string(),
+        KeyCode::Enter => "enter".to_string(),
+        KeyCode::Tab => "tab".to_string(),
+        KeyCode::Backspace => "backspace".to_string(),
+        KeyCode::Delete => "delete".to_string(),
+        KeyCode::Home => "home".to_string(),
+        KeyCode::End => "end".to_string(),
+        KeyCode::PageUp => "pageup".to_string(),
+        KeyCode::PageDown => "pagedown".to_string(),
+        other => other.to_string(),
+    }
+}
+
+fn get_user_config_path() -> Option<PathBuf> {
+    let config_dir = match env::var_os("XDG_CONFIG_HOME").filter(|dir| !dir.is_empty()) {
+        Some(dir) => PathBuf::from(dir),
+        None => PathBuf::from(env::var_os("HOME")?).join(".config"),
+    };
+    Some(config_dir.join("git-ui").join("config.toml"))
+}
+
+/// copy `from` into `into`, sub tables are merged instead of replaced
+fn merge_tables(into: &mut Table, from: Table) {
+    for (key, value) in from {
+        match (into.get_mut(&key), value) {
+            (Some(toml::Value::Table(into_table)), toml::Value::Table(from_table)) => {
+                merge_tables(into_table, from_table)
+            }
+            (_, value) => {
+                into.insert(key, value);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// load `content` as the repository config of a scratch git dir
+    fn load(name: &str, content: &str) -> Result<Config, String> {
+        let git_dir =
+            env::temp_dir().join(format!("git-ui-config-{}-{}", name, std::process::id()));
+        fs::create_dir_all(&git_dir).unwrap();
+        fs::write(git_dir.join("git-ui.toml"), content).unwrap();
+        // an empty user file keeps the config of the machine out of the test
+        let user_file = git_dir.join("user.toml");
+        fs::write(&user_file, "").unwrap();
+        let config = Config::load(Some(&user_file), &git_dir);
+        fs::remove_dir_all(&git_dir).unwrap();
+        config
+    }
+
+    #[test]
+    fn refresh_interval_has_a_minimum() {
+        assert!(load("zero", "refresh_interval = 0")
+            .err()
+            .unwrap()
+            .starts_with("refresh_interval must be at least"));
+        assert!(load("low", "refresh_interval = 19").is_err());
+        let config = load("min", "refresh_interval = 20").unwrap();
+        assert_eq!(config.refresh_interval, 20);
+        assert_eq!(load("default", "").unwrap().refresh_interval, 100);
+    }
+
+    #[test]
+    fn load_rejects_unknown_actions_and_keys() {
+        let error = load("action", "[keys.status]\nfly = \"f\"").err().unwrap();
+        assert_eq!(error, "Unknown action 'fly' in [keys.status]");
+        let error = load("key", "[keys.status]\nadd = \"hyper\"").err().unwrap();
+        assert_eq!(error, "Unknown key 'hyper' for 'add'");
+        let error = load("reserved", "[keys.status]\nadd = \"?\"")
+            .err()
+            .unwrap();
+        assert_eq!(
+            error,
+            "'?' opens the help or the palette, cannot bind 'add'"
+        );
+    }
+
+    #[test]
+    fn load_rejects_the_keys_of_every_page() {
+        let error = load("quit", "[keys.log]\nrevert = \"q\"").err().unwrap();
+        assert_eq!(error, "'q' quits or switches pages, cannot bind 'revert'");
+        for page in ["1", "6"] {
+            let content = format!("[keys.stash]\ndrop = \"{}\"", page);
+            let error = load(&format!("page{}", page), &content).err().unwrap();
+            assert!(error.starts_with(&format!("'{}' quits or switches pages", page)));
+        }
+        // other digits and an uppercase Q are free
+        assert!(load("free", "[keys.stash]\ndrop = \"7\"\npop = \"Q\"").is_ok());
+    }
+
+    #[test]
+    fn merge_tables_merges_sub_tables() {
+        let mut into: Table = "refresh_interval = 100\n[keys.status]\nadd = \"x\"\ncommit = \"C\""
+            .parse()
+            .unwrap();
+        let from: Table =
+            "refresh_interval = 50\n[keys.status]\nadd = \"y\"\n[keys.log]\nrevert = \"R\""
+                .parse()
+                .unwrap();
+        merge_tables(&mut into, from);
+
+        assert_eq!(into["refresh_interval"].as_integer(), Some(50));
+        let status = into["keys"]["status"].as_table().unwrap();
+        assert_eq!(status["add"].as_str(), Some("y"));
+        assert_eq!(status["commit"].as_str(), Some("C"));
+        assert_eq!(into["keys"]["log"]["revert"].as_str(), Some("R"));
+    }
+
+    #[test]
+    fn parse_key_names() {
+        assert_eq!(parse_key("a"), Some(KeyCode::Char('a')));
+        assert_eq!(parse_key("A"), Some(KeyCode::Char('A')));
+        assert_eq!(parse_key("é"), Some(KeyCode::Char('é')));
+        assert_eq!(parse_key("space"), Some(KeyCode::Char(' ')));
+        assert_eq!(parse_key("Enter"), Some(KeyCode::Enter));
+        assert_eq!(parse_key("PageDown"), Some(KeyCode::PageDown));
+        assert_eq!(parse_key("ctrl-a"), None);
+        assert_eq!(parse_key(""), None);
+    }
+
+    #[test]
+    fn get_key_name_reverses_parse_key() {
+        for name in ["a", "space", "enter", "tab", "home", "pageup"] {
+            assert_eq!(get_key_name(parse_key(name).unwrap()), name);
+        }
+    }
+
+    #[test]
+    fn get_action_follows_the_bindings() {
+        let mut config = Config::default();
+        config
+            .keys
+            .status
+            .insert("add".to_string(), "x".to_string());
+        config
+            .keys
+            .status
+            .insert("tree".to_string(), "c".to_string());
+        let page = Pages::StatusPAGE;
+        let get_name = |block, code| {
+            config
+                .get_action(&page, block, code)
+                .map(|action| action.name)
+        };
+
+        // the new key runs the action, the built-in one no longer does
+        assert_eq!(get_name(Some("unstaged"), KeyCode::Char('x')), Some("add"));
+        assert_eq!(get_name(Some("unstaged"), KeyCode::Char('a')), None);
+        // outside the blocks of the action neither key runs it
+        assert_eq!(get_name(Some("staged"), KeyCode::Char('x')), None);
+        assert_eq!(
+            get_name(Some("staged"), KeyCode::Char('r')),
+            Some("restore")
+        );
+        // a key given by the configuration wins over the built-in one of another action
+        assert_eq!(get_name(Some("unstaged"), KeyCode::Char('c')), Some("tree"));
+        assert_eq!(
+            get_name(Some("diff"), KeyCode::Char('d')),
+            Some("discard_hunk")
+        );
+        assert_eq!(get_name(Some("unstaged"), KeyCode::Down), None);
+    }
+}
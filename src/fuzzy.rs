@@ -0,0 +1,42 @@
+/// positions (in chars) of the `pattern` chars found in order inside `text`, ignoring case.
+/// None when `text` does not contain every char of `pattern`
+pub fn fuzzy_match(pattern: &str, text: &str) -> Option<Vec<usize>> {
+    let mut pattern_chars = pattern.chars().map(lowercase).peekable();
+    let mut indices = Vec::new();
+
+    for (i, text_char) in text.chars().enumerate() {
+        match pattern_chars.peek() {
+            Some(pattern_char) if *pattern_char == lowercase(text_char) => {
+                indices.push(i);
+                pattern_chars.next();
+            }
+            Some(_) => {}
+            None => break,
+        }
+    }
+
+    if pattern_chars.peek().is_none() {
+        Some(indices)
+    } else {
+        None
+    }
+}
+
+/// like `fuzzy_match`, preferring the first place where `pattern` is found in one piece
+pub fn tight_match(pattern: &str, text: &str) -> Option<Vec<usize>> {
+    let pattern: Vec<char> = pattern.chars().map(lowercase).collect();
+    let text_chars: Vec<char> = text.chars().map(lowercase).collect();
+    if !pattern.is_empty() {
+        let start = text_chars
+            .windows(pattern.len())
+            .position(|window| window == pattern.as_slice());
+        if let Some(start) = start {
+            return Some((start..start + pattern.len()).collect());
+        }
+    }
+    fuzzy_match(&pattern.iter().collect::<String>(), text)
+}
+
+fn lowercase(c: char) -> char {
+    c.to_lowercase().next().unwrap_or(c)
+}
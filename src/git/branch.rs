@@ -1,8 +1,89 @@
-use git2::{build::CheckoutBuilder, BranchType, Error as GitError, Repository};
+use git2::{
+    build::CheckoutBuilder, BranchType, CheckoutNotificationType, Error as GitError, ErrorCode,
+    Oid, Repository, Signature, StashFlags,
+};
+
+use crate::git::{apply_stash, get_short_id, CommitInfo};
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum HeadState {
+    OnBranch,
+    // HEAD points to a commit, `current` holds its short id
+    Detached,
+    // fresh repository: `current` names the branch the first commit will create
+    Unborn,
+}
+
+/// upstream and last commit of a local branch
+#[derive(Clone, Default)]
+pub struct BranchInfo {
+    /// "origin/main", None when the branch tracks nothing
+    pub upstream: Option<String>,
+    /// the upstream is configured but its remote branch no longer exists
+    pub gone: bool,
+    pub ahead: usize,
+    pub behind: usize,
+    pub last_commit: Option<CommitInfo>,
+}
+
+impl BranchInfo {
+    /// "origin/main ↑2 ↓1", "origin/main: gone", None without upstream
+    pub fn get_tracking_text(&self) -> Option<String> {
+        let upstream = self.upstream.as_ref()?;
+        if self.gone {
+            return Some(format!("{}: gone", upstream));
+        }
+        let mut text = upstream.clone();
+        if self.ahead > 0 {
+            text.push_str(&format!(" ↑{}", self.ahead));
+        }
+        if self.behind > 0 {
+            text.push_str(&format!(" ↓{}", self.behind));
+        }
+        Some(text)
+    }
+}
+
+/// what to do with local changes the checkout would overwrite
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum CheckoutMode {
+    /// refuse to switch
+    Safe,
+    /// stash them, switch, then pop them, the stash stays unless they apply cleanly
+    Stash,
+    /// bring them along like `git checkout --merge`, conflicts are left in the files
+    Carry,
+}
+
+/// branch to switch to
+#[derive(Clone)]
+pub enum CheckoutTarget {
+    Local(String),
+    /// remote branch ("upstream/feature") and the name of the local branch to create,
+    /// None to reuse the one tracking it or to take the name it has on the remote
+    Remote(String, Option<String>),
+}
+
+pub enum CheckoutOutcome {
+    Switched,
+    /// nothing changed: a local branch already has the name of the remote one and tracks
+    /// something else, the local branch needs another name
+    NameTaken(String),
+    /// nothing changed: the local changes of these files would be overwritten
+    Blocked(Vec<String>),
+    /// switched, but the changes brought along conflict in these files
+    Conflicts(Vec<String>),
+    /// the stashed changes could not be applied: they stay in stash@{0}. Whether the switch
+    /// happened, the reason, then the files that blocked them
+    StashKept(bool, String, Vec<String>),
+}
 
 pub struct Branch {
     pub current: String,
+    pub head: HeadState,
     pub local_branches: Vec<String>,
+    /// tracking and last commit of `local_branches`, same order
+    pub local_infos: Vec<BranchInfo>,
     pub remote_branches: Vec<String>,
 }
 
@@ -15,65 +96,286 @@ impl Branch {
                 vec!["failed to get branches".to_string()],
             ),
         };
+        let infos = locals
+            .iter()
+            .map(|name| Branch::get_branch_info(repo, name))
+            .collect();
 
+        let (current, head) = Branch::get_head_state(repo);
         Branch {
-            current: match Branch::get_current_branch_name(repo) {
-                Ok(branch_name) => branch_name,
-                Err(e) => {
-                    println!("Error: {}", e);
-                    std::process::exit(1);
-                }
-            },
+            current,
+            head,
             local_branches: locals,
+            local_infos: infos,
             remote_branches: remotes,
         }
     }
 
+    /// tracking of the checked out branch, None on a detached or unborn HEAD
+    pub fn get_current_info(&self) -> Option<&BranchInfo> {
+        if self.head != HeadState::OnBranch {
+            return None;
+        }
+        let pos = self
+            .local_branches
+            .iter()
+            .position(|name| *name == self.current)?;
+        self.local_infos.get(pos)
+    }
+
+    fn get_branch_info(repo: &Repository, name: &str) -> BranchInfo {
+        let Ok(branch) = repo.find_branch(name, BranchType::Local) else {
+            return BranchInfo::default();
+        };
+        let mut info = BranchInfo {
+            last_commit: branch
+                .get()
+                .peel_to_commit()
+                .ok()
+                .map(|commit| CommitInfo::from_commit(&commit)),
+            ..BranchInfo::default()
+        };
+        match branch.upstream() {
+            Ok(upstream) => {
+                info.upstream = upstream.name().ok().flatten().map(str::to_string);
+                if let (Some(local), Some(remote)) =
+                    (branch.get().target(), upstream.get().target())
+                    && let Ok((ahead, behind)) = repo.graph_ahead_behind(local, remote)
+                {
+                    info.ahead = ahead;
+                    info.behind = behind;
+                }
+            }
+            // configured in branch.<name>.merge, but the remote branch was deleted
+            Err(_) => {
+                let refname = format!("refs/heads/{}", name);
+                if let Ok(upstream) = repo.branch_upstream_name(&refname)
+                    && let Some(upstream) = upstream.as_str()
+                {
+                    let short = upstream
+                        .strip_prefix("refs/remotes/")
+                        .or_else(|| upstream.strip_prefix("refs/heads/"))
+                        .unwrap_or(upstream);
+                    info.upstream = Some(short.to_string());
+                    info.gone = true;
+                }
+            }
+        }
+        info
+    }
+
+    /// switch to `target`, a remote branch goes through the local branch tracking it, fast-forwarded
+    /// when it is behind, or a new one. Local changes are never overwritten: `mode` says what to
+    /// do with them
     pub fn checkout(
         &mut self,
-        btype: BranchType,
-        pos: usize,
-        repo: &Repository,
-    ) -> Result<(), GitError> {
-        let branch: &str = match btype {
-            BranchType::Remote => &self.remote_branches[pos],
-            BranchType::Local => &self.local_branches[pos],
+        target: &CheckoutTarget,
+        mode: CheckoutMode,
+        repo: &mut Repository,
+    ) -> Result<CheckoutOutcome, GitError> {
+        let (target_id, local_name, upstream, fast_forward) = match target {
+            CheckoutTarget::Local(name) => (
+                repo.revparse_single(&format!("refs/heads/{}", name))?.id(),
+                name.clone(),
+                None,
+                false,
+            ),
+            CheckoutTarget::Remote(remote_branch, local_name) => {
+                let remote_tip = repo
+                    .find_branch(remote_branch, BranchType::Remote)?
+                    .get()
+                    .peel_to_commit()?
+                    .id();
+                match local_name {
+                    Some(local_name) => {
+                        (remote_tip, local_name.clone(), Some(remote_branch), false)
+                    }
+                    None => match Branch::find_tracking_branch(repo, remote_branch)? {
+                        Some((local_name, local_tip)) => {
+                            // a diverged branch is checked out as it is, never rewritten
+                            let behind = local_tip != remote_tip
+                                && repo.graph_descendant_of(remote_tip, local_tip)?;
+                            let tip = if behind { remote_tip } else { local_tip };
+                            (tip, local_name, None, behind)
+                        }
+                        None => {
+                            let (_, short_name) = Branch::split_remote_branch(repo, remote_branch)?;
+                            if repo.find_branch(&short_name, BranchType::Local).is_ok() {
+                                return Ok(CheckoutOutcome::NameTaken(short_name));
+                            }
+                            (remote_tip, short_name, Some(remote_branch), false)
+                        }
+                    },
+                }
+            }
         };
-        let mut checkout_builder = CheckoutBuilder::new();
 
-        checkout_builder
-            .allow_conflicts(true)
-            .conflict_style_merge(true);
-
-        match btype {
-            BranchType::Local => {
-                // Checkout existing local branch
-                let obj = repo.revparse_single(&format!("refs/heads/{}", branch))?;
-                repo.checkout_tree(&obj, Some(&mut checkout_builder))?;
-                repo.set_head(&format!("refs/heads/{}", branch))?;
+        // the tracking branch exists before anything is touched, a failure leaves no trace
+        if let Some(upstream) = upstream {
+            let commit = repo.find_commit(target_id)?;
+            let mut local_branch = repo.branch(&local_name, &commit, false)?;
+            if let Err(e) = local_branch.set_upstream(Some(upstream)) {
+                local_branch.delete()?;
+                return Err(e);
             }
-            BranchType::Remote => {
-                // Find remote branch by name
-                let remote_branch = repo.find_branch(branch, git2::BranchType::Remote)?;
-                let commit = remote_branch.get().peel_to_commit()?;
-
-                // Create local tracking branch
-                let local_name = branch.strip_prefix("origin/").unwrap_or(branch);
-                let local_branch = repo.branch(local_name, &commit, false)?;
-
-                // Set upstream
-                let mut local_branch = local_branch;
-                local_branch.set_upstream(Some(branch))?;
-
-                // Checkout
-                repo.checkout_tree(commit.as_object(), Some(&mut checkout_builder))?;
-                repo.set_head(&format!("refs/heads/{}", local_name))?;
+        }
+        let created = upstream.is_some();
+
+        let stashed = match mode {
+            CheckoutMode::Safe => false,
+            CheckoutMode::Stash | CheckoutMode::Carry => {
+                let signature = repo
+                    .signature()
+                    .or_else(|_| Signature::now("git-ui", "git-ui"))?;
+                let message = format!("git-ui: checkout of {}", local_name);
+                match repo.stash_save(&signature, &message, Some(StashFlags::INCLUDE_UNTRACKED)) {
+                    Ok(_) => true,
+                    // a clean tree has nothing to stash
+                    Err(e) if e.code() == ErrorCode::NotFound => false,
+                    Err(e) => {
+                        if created {
+                            repo.find_branch(&local_name, BranchType::Local)?.delete()?;
+                        }
+                        return Err(e);
+                    }
+                }
             }
         };
 
+        let mut blocked = Vec::new();
+        let refname = format!("refs/heads/{}", local_name);
+        let switched = Branch::switch_tree(repo, target_id, &mut blocked).and_then(|()| {
+            if fast_forward {
+                repo.find_reference(&refname)?
+                    .set_target(target_id, "checkout: fast-forward")?;
+            }
+            repo.set_head(&refname)
+        });
+        if let Err(e) = switched {
+            // the created branch goes whatever happens to the changes
+            let deleted = if created {
+                repo.find_branch(&local_name, BranchType::Local)
+                    .and_then(|mut branch| branch.delete())
+            } else {
+                Ok(())
+            };
+            // put the changes back where they were
+            if stashed && let Err(pop_error) = repo.stash_pop(0, None) {
+                return Ok(CheckoutOutcome::StashKept(
+                    false,
+                    pop_error.message().to_string(),
+                    Vec::new(),
+                ));
+            }
+            deleted?;
+            return match e.code() {
+                ErrorCode::Conflict if !blocked.is_empty() => Ok(CheckoutOutcome::Blocked(blocked)),
+                _ => Err(e),
+            };
+        }
+        if !stashed {
+            return Ok(CheckoutOutcome::Switched);
+        }
+
+        // bring the changes onto the new branch, a conflict leaves markers in the files
+        let mut conflicts = Vec::new();
+        if let Err(e) = apply_stash(repo, 0, &mut conflicts) {
+            // HEAD has moved already, the changes wait in the stash
+            return Ok(CheckoutOutcome::StashKept(
+                true,
+                e.message().to_string(),
+                conflicts,
+            ));
+        }
+        if conflicts.is_empty() || mode == CheckoutMode::Carry {
+            repo.stash_drop(0)?;
+        }
+        if conflicts.is_empty() {
+            Ok(CheckoutOutcome::Switched)
+        } else {
+            Ok(CheckoutOutcome::Conflicts(conflicts))
+        }
+    }
+
+    /// "upstream/feature/x" -> ("upstream", "feature/x"), the remote is the one whose fetch
+    /// refspec maps the branch, not the text before the first slash
+    pub fn split_remote_branch(
+        repo: &Repository,
+        remote_branch: &str,
+    ) -> Result<(String, String), GitError> {
+        let remote_name = repo.branch_remote_name(&format!("refs/remotes/{}", remote_branch))?;
+        let remote_name = remote_name
+            .as_str()
+            .ok_or_else(|| GitError::from_str("Remote name is not valid UTF-8"))?
+            .to_string();
+        let branch = remote_branch
+            .strip_prefix(&format!("{}/", remote_name))
+            .unwrap_or(remote_branch)
+            .to_string();
+        if branch == "HEAD" {
+            return Err(GitError::from_str(&format!(
+                "{} is the default branch pointer of {}, not a branch",
+                remote_branch, remote_name
+            )));
+        }
+        Ok((remote_name, branch))
+    }
+
+    /// local branch whose upstream is `remote_branch`, with its tip
+    fn find_tracking_branch(
+        repo: &Repository,
+        remote_branch: &str,
+    ) -> Result<Option<(String, Oid)>, GitError> {
+        for branch in repo.branches(Some(BranchType::Local))? {
+            let (branch, _) = branch?;
+            let Ok(upstream) = branch.upstream() else {
+                continue;
+            };
+            if upstream.name()? == Some(remote_branch)
+                && let (Some(name), Some(tip)) = (branch.name()?, branch.get().target())
+            {
+                return Ok(Some((name.to_string(), tip)));
+            }
+        }
+        Ok(None)
+    }
+
+    /// check out the tree of `target` unless it overwrites local changes, HEAD is left as is
+    pub fn checkout_tree_safely(target: Oid, repo: &Repository) -> Result<(), GitError> {
+        let mut blocked = Vec::new();
+        if let Err(e) = Branch::switch_tree(repo, target, &mut blocked) {
+            if blocked.is_empty() {
+                return Err(e);
+            }
+            return Err(GitError::from_str(&format!(
+                "Local changes would be overwritten: {}",
+                blocked.join(", ")
+            )));
+        }
         Ok(())
     }
 
+    /// check out the tree of `target` unless it overwrites local changes,
+    /// the files that prevent it are pushed to `blocked`
+    fn switch_tree(
+        repo: &Repository,
+        target: Oid,
+        blocked: &mut Vec<String>,
+    ) -> Result<(), GitError> {
+        let target = repo.find_object(target, None)?;
+        let mut checkout_builder = CheckoutBuilder::new();
+        checkout_builder
+            .safe()
+            .notify_on(CheckoutNotificationType::CONFLICT)
+            .notify(|_, path, _, _, _| {
+                if let Some(path) = path {
+                    blocked.push(path.display().to_string());
+                }
+                true
+            });
+        repo.checkout_tree(&target, Some(&mut checkout_builder))
+    }
+
     pub fn delete_branch(&mut self, branch_name: &str, repo: &Repository) -> Result<(), GitError> {
         if branch_name == self.current {
             return Err(git2::Error::from_str("Cannot delete the current branch"));
@@ -87,7 +389,73 @@ impl Branch {
         Ok(())
     }
 
+    /// commits of `branch_name` reachable neither from HEAD nor from its upstream, newest first:
+    /// the work `git branch -d` refuses to drop
+    pub fn get_unmerged_commits(
+        &self,
+        branch_name: &str,
+        repo: &Repository,
+    ) -> Result<Vec<Oid>, GitError> {
+        let branch = repo.find_branch(branch_name, BranchType::Local)?;
+        let tip = branch.get().peel_to_commit()?.id();
+        let mut revwalk = repo.revwalk()?;
+        revwalk.push(tip)?;
+        if let Ok(head) = repo.head()
+            && let Some(head) = head.target()
+        {
+            revwalk.hide(head)?;
+        }
+        if let Ok(upstream) = branch.upstream()
+            && let Some(upstream) = upstream.get().target()
+        {
+            revwalk.hide(upstream)?;
+        }
+        revwalk.collect()
+    }
+
+    /// rename a local branch, git2 moves its `branch.<name>` config section and HEAD along
+    pub fn rename_branch(
+        &mut self,
+        old_name: &str,
+        new_name: &str,
+        repo: &Repository,
+    ) -> Result<(), GitError> {
+        let mut branch = repo.find_branch(old_name, BranchType::Local)?;
+        branch.rename(new_name, false)?;
+        Ok(())
+    }
+
+    /// make the local branch `branch_name` track the remote branch `upstream` ("origin/main")
+    pub fn set_upstream(
+        &mut self,
+        branch_name: &str,
+        upstream: &str,
+        repo: &Repository,
+    ) -> Result<(), GitError> {
+        let mut branch = repo.find_branch(branch_name, BranchType::Local)?;
+        branch.set_upstream(Some(upstream))
+    }
+
+    pub fn unset_upstream(&mut self, branch_name: &str, repo: &Repository) -> Result<(), GitError> {
+        if repo
+            .branch_upstream_name(&format!("refs/heads/{}", branch_name))
+            .is_err()
+        {
+            return Err(GitError::from_str(&format!(
+                "{} tracks no branch",
+                branch_name
+            )));
+        }
+        let mut branch = repo.find_branch(branch_name, BranchType::Local)?;
+        branch.set_upstream(None)
+    }
+
     pub fn create_branch(&mut self, branch_name: &str, repo: &Repository) -> Result<(), GitError> {
+        if self.head == HeadState::Unborn {
+            return Err(GitError::from_str(
+                "No commit yet: the first commit creates the branch",
+            ));
+        }
         let head = repo.head()?;
         let last_commit = head.peel_to_commit()?;
         match repo.branch(branch_name, &last_commit, false) {
@@ -97,13 +465,26 @@ impl Branch {
         Ok(())
     }
 
-    fn get_current_branch_name(repo: &Repository) -> Result<String, GitError> {
-        let head = repo.head()?;
-
-        if let Some(name) = head.shorthand() {
-            Ok(name.to_string())
-        } else {
-            Err(GitError::from_str("Unable to get branch name"))
+    fn get_head_state(repo: &Repository) -> (String, HeadState) {
+        match repo.head() {
+            Ok(head) if repo.head_detached().unwrap_or(false) => {
+                let id = head.target().map(get_short_id).unwrap_or_default();
+                (id, HeadState::Detached)
+            }
+            Ok(head) => (
+                head.shorthand().unwrap_or("HEAD").to_string(),
+                HeadState::OnBranch,
+            ),
+            // HEAD names a branch that has no commit yet
+            Err(_) => {
+                let name = repo
+                    .find_reference("HEAD")
+                    .ok()
+                    .and_then(|head| head.symbolic_target().map(str::to_string))
+                    .unwrap_or_default();
+                let name = name.strip_prefix("refs/heads/").unwrap_or(&name);
+                (name.to_string(), HeadState::Unborn)
+            }
         }
     }
 
@@ -125,3 +506,247 @@ impl Branch {
         Ok((remote_branches, local_branches))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{
+        env, fs,
+        path::{Path, PathBuf},
+    };
+
+    /// commit `content` as the file `name` on top of HEAD, on `update_ref` when given
+    fn commit_file(repo: &Repository, update_ref: Option<&str>, name: &str, content: &str) -> Oid {
+        let workdir = repo.workdir().unwrap();
+        fs::write(workdir.join(name), content).unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new(name)).unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = Signature::now("test", "test@example.com").unwrap();
+        let parent = repo.head().ok().and_then(|head| head.peel_to_commit().ok());
+        let parents: Vec<&git2::Commit> = parent.iter().collect();
+        repo.commit(update_ref, &signature, &signature, name, &tree, &parents)
+            .unwrap()
+    }
+
+    /// `main` checked out with `shared` = "base", `other` one commit ahead with
+    /// `shared` = "other", and `shared` changed to "local" in the working tree
+    fn diverging_repo(name: &str) -> Repository {
+        let workdir =
+            env::temp_dir().join(format!("git-ui-checkout-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&workdir);
+        let repo = Repository::init(&workdir).unwrap();
+        repo.set_head("refs/heads/main").unwrap();
+        let base = commit_file(&repo, Some("HEAD"), "shared", "base\n");
+        repo.branch("other", &repo.find_commit(base).unwrap(), false)
+            .unwrap();
+        commit_file(&repo, Some("refs/heads/other"), "shared", "other\n");
+        // the commit above only moved `other`: put the index and the file back on main
+        repo.reset(
+            repo.find_commit(base).unwrap().as_object(),
+            git2::ResetType::Hard,
+            None,
+        )
+        .unwrap();
+        fs::write(workdir.join("shared"), "local\n").unwrap();
+        repo
+    }
+
+    fn read(repo: &Repository, name: &str) -> String {
+        fs::read_to_string(repo.workdir().unwrap().join(name)).unwrap()
+    }
+
+    fn head_name(repo: &Repository) -> String {
+        repo.head().unwrap().shorthand().unwrap().to_string()
+    }
+
+    fn count_stashes(repo: &mut Repository) -> usize {
+        let mut count = 0;
+        repo.stash_foreach(|_, _, _| {
+            count += 1;
+            true
+        })
+        .unwrap();
+        count
+    }
+
+    fn cleanup(repo: Repository) {
+        let workdir = repo.workdir().unwrap().to_path_buf();
+        drop(repo);
+        fs::remove_dir_all(workdir).unwrap();
+    }
+
+    #[test]
+    fn safe_checkout_is_blocked_by_local_changes() {
+        let mut repo = diverging_repo("blocked");
+        let mut branch = Branch::new(&repo);
+        let target = CheckoutTarget::Local("other".to_string());
+        match branch
+            .checkout(&target, CheckoutMode::Safe, &mut repo)
+            .unwrap()
+        {
+            CheckoutOutcome::Blocked(paths) => assert_eq!(paths, vec!["shared".to_string()]),
+            _ => panic!("the checkout should be blocked"),
+        }
+        assert_eq!(head_name(&repo), "main");
+        assert_eq!(read(&repo, "shared"), "local\n");
+        cleanup(repo);
+    }
+
+    #[test]
+    fn blocked_remote_checkout_leaves_no_branch() {
+        let mut repo = diverging_repo("remote-blocked");
+        repo.remote("origin", "https://example.com/repo.git")
+            .unwrap();
+        let other = repo.refname_to_id("refs/heads/other").unwrap();
+        repo.reference("refs/remotes/origin/feature", other, false, "test")
+            .unwrap();
+        let mut branch = Branch::new(&repo);
+        let target = CheckoutTarget::Remote("origin/feature".to_string(), None);
+        let outcome = branch.checkout(&target, CheckoutMode::Safe, &mut repo);
+        assert!(matches!(outcome, Ok(CheckoutOutcome::Blocked(_))));
+        assert!(repo.find_branch("feature", BranchType::Local).is_err());
+        assert_eq!(head_name(&repo), "main");
+        cleanup(repo);
+    }
+
+    #[test]
+    fn stash_checkout_keeps_the_stash_of_a_conflict() {
+        let mut repo = diverging_repo("stash");
+        let mut branch = Branch::new(&repo);
+        let target = CheckoutTarget::Local("other".to_string());
+        match branch
+            .checkout(&target, CheckoutMode::Stash, &mut repo)
+            .unwrap()
+        {
+            CheckoutOutcome::Conflicts(paths) => assert_eq!(paths, vec!["shared".to_string()]),
+            _ => panic!("the stashed change conflicts with other"),
+        }
+        assert_eq!(head_name(&repo), "other");
+        assert!(read(&repo, "shared").contains("<<<<<<<"));
+        assert_eq!(count_stashes(&mut repo), 1);
+        cleanup(repo);
+    }
+
+    #[test]
+    fn carry_checkout_drops_the_stash_and_leaves_the_markers() {
+        let mut repo = diverging_repo("carry");
+        let mut branch = Branch::new(&repo);
+        let target = CheckoutTarget::Local("other".to_string());
+        let outcome = branch.checkout(&target, CheckoutMode::Carry, &mut repo);
+        assert!(matches!(outcome, Ok(CheckoutOutcome::Conflicts(_))));
+        assert_eq!(head_name(&repo), "other");
+        assert!(read(&repo, "shared").contains("local"));
+        assert_eq!(count_stashes(&mut repo), 0);
+        cleanup(repo);
+    }
+
+    #[test]
+    fn carry_checkout_of_unrelated_changes_switches_cleanly() {
+        let mut repo = diverging_repo("clean");
+        fs::write(repo.workdir().unwrap().join("shared"), "base\n").unwrap();
+        fs::write(repo.workdir().unwrap().join("notes"), "draft\n").unwrap();
+        let mut branch = Branch::new(&repo);
+        let target = CheckoutTarget::Local("other".to_string());
+        let outcome = branch.checkout(&target, CheckoutMode::Carry, &mut repo);
+        assert!(matches!(outcome, Ok(CheckoutOutcome::Switched)));
+        assert_eq!(head_name(&repo), "other");
+        assert_eq!(read(&repo, "shared"), "other\n");
+        assert_eq!(read(&repo, "notes"), "draft\n");
+        cleanup(repo);
+    }
+
+    /// `origin` with `main` and `feature` (adding `feature.txt`), cloned next to it: the clone
+    /// has `main` only, `origin/feature` is a remote branch
+    fn clone_with_feature(name: &str) -> (PathBuf, Repository, Repository) {
+        let root = env::temp_dir().join(format!("git-ui-remote-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let origin = Repository::init(root.join("origin")).unwrap();
+        origin.set_head("refs/heads/main").unwrap();
+        let base = commit_file(&origin, Some("HEAD"), "readme", "hello\n");
+        origin
+            .branch("feature", &origin.find_commit(base).unwrap(), false)
+            .unwrap();
+        origin.set_head("refs/heads/feature").unwrap();
+        commit_file(&origin, Some("HEAD"), "feature.txt", "1\n");
+        origin.set_head("refs/heads/main").unwrap();
+
+        let clone =
+            Repository::clone(root.join("origin").to_str().unwrap(), root.join("clone")).unwrap();
+        (root, origin, clone)
+    }
+
+    fn tracking_config(repo: &Repository, branch: &str) -> (String, String) {
+        let config = repo.config().unwrap().snapshot().unwrap();
+        let get = |key: &str| {
+            config
+                .get_str(&format!("branch.{}.{}", branch, key))
+                .unwrap()
+                .to_string()
+        };
+        (get("remote"), get("merge"))
+    }
+
+    #[test]
+    fn remote_checkout_creates_a_tracking_branch() {
+        let (root, _origin, mut clone) = clone_with_feature("create");
+        assert!(clone.find_branch("feature", BranchType::Local).is_err());
+        let mut branch = Branch::new(&clone);
+        let target = CheckoutTarget::Remote("origin/feature".to_string(), None);
+        let outcome = branch.checkout(&target, CheckoutMode::Safe, &mut clone);
+        assert!(matches!(outcome, Ok(CheckoutOutcome::Switched)));
+
+        let remote_tip = clone.refname_to_id("refs/remotes/origin/feature").unwrap();
+        assert_eq!(
+            clone.refname_to_id("refs/heads/feature").unwrap(),
+            remote_tip
+        );
+        assert_eq!(clone.head().unwrap().name(), Some("refs/heads/feature"));
+        assert_eq!(read(&clone, "feature.txt"), "1\n");
+        assert_eq!(
+            tracking_config(&clone, "feature"),
+            ("origin".to_string(), "refs/heads/feature".to_string())
+        );
+        drop(clone);
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn remote_checkout_fast_forwards_the_tracking_branch() {
+        let (root, origin, mut clone) = clone_with_feature("forward");
+        let mut branch = Branch::new(&clone);
+        let target = CheckoutTarget::Remote("origin/feature".to_string(), None);
+        branch
+            .checkout(&target, CheckoutMode::Safe, &mut clone)
+            .unwrap();
+        let main = CheckoutTarget::Local("main".to_string());
+        branch
+            .checkout(&main, CheckoutMode::Safe, &mut clone)
+            .unwrap();
+        assert!(!clone.workdir().unwrap().join("feature.txt").exists());
+
+        // feature moves on the remote and is fetched
+        origin.set_head("refs/heads/feature").unwrap();
+        origin
+            .checkout_head(Some(CheckoutBuilder::new().force()))
+            .unwrap();
+        let new_tip = commit_file(&origin, Some("HEAD"), "feature.txt", "2\n");
+        clone
+            .find_remote("origin")
+            .unwrap()
+            .fetch(&[] as &[&str], None, None)
+            .unwrap();
+
+        let outcome = branch.checkout(&target, CheckoutMode::Safe, &mut clone);
+        assert!(matches!(outcome, Ok(CheckoutOutcome::Switched)));
+        assert_eq!(clone.refname_to_id("refs/heads/feature").unwrap(), new_tip);
+        assert_eq!(clone.head().unwrap().name(), Some("refs/heads/feature"));
+        assert_eq!(read(&clone, "feature.txt"), "2\n");
+        // no second branch was created for the same remote branch
+        let locals = clone.branches(Some(BranchType::Local)).unwrap().count();
+        assert_eq!(locals, 2);
+        drop((origin, clone));
+        fs::remove_dir_all(root).unwrap();
+    }
+}
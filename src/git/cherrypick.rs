@@ -0,0 +1,115 @@
+use git2::{Error as GitError, Oid, Repository};
+
+use crate::{
+    git::{get_short_id, Commit, Git},
+    pages::Pages,
+};
+
+pub trait CherryPick {
+    fn cherry_pick(&mut self, ids: &[Oid], add_trailer: bool) -> Result<(), GitError>;
+
+    fn revert_commit(&mut self, id: Oid) -> Result<(), GitError>;
+}
+
+impl CherryPick for Git {
+    /// apply `ids` (oldest first) on top of HEAD.
+    /// Every commit but the last one is committed right away, the last one opens the
+    /// commit popup so its message can be edited. Stops at the first conflict.
+    fn cherry_pick(&mut self, ids: &[Oid], add_trailer: bool) -> Result<(), GitError> {
+        for (i, id) in ids.iter().enumerate() {
+            let commit = self.repo.find_commit(*id)?;
+            if commit.parent_count() > 1 {
+                return Err(GitError::from_str(&format!(
+                    "{} is a merge commit, cherry-pick it from the command line",
+                    get_short_id(*id)
+                )));
+            }
+            self.repo.cherrypick(&commit, None)?;
+
+            let mut message = commit.message().unwrap_or("").trim_end().to_string();
+            if add_trailer {
+                message.push_str(&format!("\n\n(cherry picked from commit {})", id));
+            }
+            let author = commit.author().to_owned();
+            drop(commit);
+
+            let conflicts = get_conflicted_paths(&self.repo)?;
+            let is_last = i + 1 == ids.len();
+            if conflicts.is_empty() && !is_last {
+                self.create_commit(&message, Some(&author))?;
+                continue;
+            }
+
+            self.commit_author = Some(author);
+            self.commit_popup.set_input(&message);
+            if conflicts.is_empty() {
+                self.commit_popup.activated = true;
+            } else {
+                let skipped: Vec<Oid> = ids[i + 1..].to_vec();
+                let lines = self.get_conflict_lines(*id, &conflicts, &skipped);
+                self.notification.show("Cherry-pick", lines);
+            }
+            return Ok(());
+        }
+        Ok(())
+    }
+
+    fn revert_commit(&mut self, id: Oid) -> Result<(), GitError> {
+        let commit = self.repo.find_commit(id)?;
+        if commit.parent_count() > 1 {
+            return Err(GitError::from_str(&format!(
+                "{} is a merge commit, revert it from the command line",
+                get_short_id(id)
+            )));
+        }
+        self.repo.revert(&commit, None)?;
+
+        let message = format!(
+            "Revert \"{}\"\n\nThis reverts commit {}.",
+            commit.summary().unwrap_or(""),
+            id
+        );
+        self.commit_author = None;
+        self.commit_popup.set_input(&message);
+
+        let conflicts = get_conflicted_paths(&self.repo)?;
+        if conflicts.is_empty() {
+            self.commit_popup.activated = true;
+        } else {
+            let lines = self.get_conflict_lines(id, &conflicts, &[]);
+            self.notification.show("Revert", lines);
+        }
+        Ok(())
+    }
+}
+
+impl Git {
+    fn get_conflict_lines(&self, id: Oid, conflicts: &[String], skipped: &[Oid]) -> Vec<String> {
+        let mut lines = vec![format!("Conflicts while applying {}:", get_short_id(id))];
+        lines.extend(conflicts.iter().map(|path| format!("  {}", path)));
+        lines.push(String::new());
+        lines.push(format!(
+            "Resolve them, add the files and commit ({}) to finish",
+            self.config
+                .get_action_key_name(&Pages::StatusPAGE, "commit")
+        ));
+        if !skipped.is_empty() {
+            let skipped: Vec<String> = skipped.iter().map(|id| get_short_id(*id)).collect();
+            lines.push(format!("Not applied: {}", skipped.join(" ")));
+        }
+        lines
+    }
+}
+
+pub fn get_conflicted_paths(repo: &Repository) -> Result<Vec<String>, GitError> {
+    let index = repo.index()?;
+    let mut paths = Vec::new();
+
+    for conflict in index.conflicts()? {
+        let conflict = conflict?;
+        if let Some(entry) = conflict.our.or(conflict.their).or(conflict.ancestor) {
+            paths.push(String::from_utf8_lossy(&entry.path).to_string());
+        }
+    }
+    Ok(paths)
+}
@@ -1,25 +1,37 @@
-use git2::{Error as GitError, Oid, Signature};
+use git2::{Error as GitError, Oid, RepositoryState, Signature};
 
 use crate::git::Git;
 
 pub trait Commit {
     fn git_commit(&self) -> Result<Oid, GitError>;
 
+    fn create_commit(&self, message: &str, author: Option<&Signature>) -> Result<Oid, GitError>;
+
     fn get_git_signature_info(&self) -> Result<(String, String), GitError>;
 }
 
 impl Commit for Git {
     fn git_commit(&self) -> Result<Oid, GitError> {
+        self.create_commit(&self.commit_popup.input, self.commit_author.as_ref())
+    }
+
+    fn create_commit(&self, message: &str, author: Option<&Signature>) -> Result<Oid, GitError> {
         // Get the current index
         let mut index = self.repo.index()?;
+        if index.has_conflicts() {
+            return Err(GitError::from_str(
+                "Resolve and stage the conflicted files before committing",
+            ));
+        }
 
         // Write the index to a tree
         let tree_id = index.write_tree()?;
         let tree = self.repo.find_tree(tree_id)?;
 
         let sig_info: (String, String) = self.get_git_signature_info()?;
-        // Create signature for author and committer
+        // Create signature for committer, the author is kept when replaying a commit
         let signature = Signature::now(&sig_info.0, &sig_info.1)?;
+        let author = author.unwrap_or(&signature);
 
         // Get the HEAD commit (parent)
         let parent_commit = match self.repo.head() {
@@ -32,28 +44,40 @@ impl Commit for Git {
             Some(parent) => {
                 // Regular commit with parent
                 self.repo.commit(
-                    Some("HEAD"),             // Update HEAD
-                    &signature,               // Author
-                    &signature,               // Committer
-                    &self.commit_popup.input, // Commit message
-                    &tree,                    // Tree
-                    &[&parent],               // Parents
+                    Some("HEAD"), // Update HEAD
+                    author,       // Author
+                    &signature,   // Committer
+                    message,      // Commit message
+                    &tree,        // Tree
+                    &[&parent],   // Parents
                 )?
             }
             None => {
                 // Initial commit (no parents)
                 self.repo.commit(
                     Some("HEAD"),
+                    author,
                     &signature,
-                    &signature,
-                    &self.commit_popup.input,
+                    message,
                     &tree,
                     &[], // No parents
                 )?
             }
         };
+
+        // a cherry-pick or a revert is over once its commit is created
+        if matches!(
+            self.repo.state(),
+            RepositoryState::CherryPick
+                | RepositoryState::CherryPickSequence
+                | RepositoryState::Revert
+                | RepositoryState::RevertSequence
+        ) {
+            self.repo.cleanup_state()?;
+        }
         Ok(commit_id)
     }
+
     fn get_git_signature_info(&self) -> Result<(String, String), GitError> {
         let config = self.repo.config()?;
 
@@ -1,6 +1,7 @@
 use std::{
     fs,
     io::Write,
+    path::Path,
     process::{Command, Output, Stdio},
 };
 
@@ -10,9 +11,16 @@ use ratatui::{
 };
 use regex::Regex;
 
-pub fn get_file_diff(file_path: &str) -> Result<Text<'static>, String> {
+use crate::config::{DiffConfig, DiffRenderer};
+
+/// `file_path` is relative to `workdir`, whatever directory the program was launched from
+pub fn get_file_diff(
+    workdir: &Path,
+    file_path: &str,
+    config: &DiffConfig,
+) -> Result<Text<'static>, String> {
     //get content in diff block
-    let output = get_diff_output(file_path)?;
+    let output = get_diff_output(workdir, file_path, config)?;
 
     //make it raw utf8
     let raw_output = String::from_utf8_lossy(&output.stdout);
@@ -22,29 +30,44 @@ pub fn get_file_diff(file_path: &str) -> Result<Text<'static>, String> {
     Ok(styled_text)
 }
 
-fn get_diff_output(file_path: &str) -> Result<Output, String> {
+fn get_diff_output(
+    workdir: &Path,
+    file_path: &str,
+    config: &DiffConfig,
+) -> Result<Output, String> {
     //launch git command one the specified file
     let mut git_args = vec!["diff"];
+    if config.renderer == DiffRenderer::Git {
+        git_args.push("--color=always");
+    }
+    git_args.push("--");
     git_args.push(file_path);
-    let git_output = match Command::new("git").args(&git_args).output() {
+    let git_output = match Command::new("git")
+        .args(&git_args)
+        .current_dir(workdir)
+        .output()
+    {
         Ok(value) => value,
         Err(_e) => return Err("Cannot launch git diff command".to_string()),
     };
 
     //if no modification. Print the file content
     if git_output.stdout.is_empty() {
-        return Err(get_file_content(file_path));
+        return Err(get_file_content(workdir, file_path));
+    }
+    if config.renderer == DiffRenderer::Git {
+        return Ok(git_output);
     }
 
     //launch delta on another std
     let mut delta_process = match Command::new("delta")
-        .args(["--line-numbers", "--syntax-theme=GitHub"])
+        .args(&config.delta_args)
         .stdin(Stdio::piped())
         .stdout(Stdio::piped())
         .spawn()
     {
         Ok(value) => value,
-        Err(_e) => return Err(get_file_content(file_path)),
+        Err(_e) => return Err(get_file_content(workdir, file_path)),
     };
 
     //write git output in delta input
@@ -62,8 +85,8 @@ fn get_diff_output(file_path: &str) -> Result<Output, String> {
     }
 }
 
-fn get_file_content(file_path: &str) -> String {
-    fs::read_to_string(file_path).unwrap_or_else(|_| "Cannot not read this file".to_string())
+fn get_file_content(workdir: &Path, file_path: &str) -> String {
+    fs::read_to_string(workdir.join(file_path)).unwrap_or_else(|_| "Cannot not read this file".to_string())
 }
 
 fn parse_delta_ansi(input: &str) -> Text<'static> {
@@ -0,0 +1,324 @@
+use git2::{
+    build::CheckoutBuilder, ApplyLocation, ApplyOptions, DiffOptions, Error as GitError, Patch,
+    Status, StatusOptions,
+};
+use std::{fmt, fs, path::Path};
+
+use crate::git::Git;
+
+/// position of a hunk between the index and the working tree, as shown by its `@@` header
+#[derive(Clone, PartialEq, Debug)]
+pub struct HunkHeader {
+    pub old_start: u32,
+    pub old_lines: u32,
+    pub new_start: u32,
+    pub new_lines: u32,
+    /// the `@@ -a,b +c,d @@ context` line
+    pub text: String,
+}
+
+impl fmt::Display for HunkHeader {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.text)
+    }
+}
+
+pub trait Discard {
+    fn discard_file(&self, filepath: &str) -> Result<(), GitError>;
+
+    fn discard_all(&self) -> Result<(), GitError>;
+
+    fn is_untracked(&self, filepath: &str) -> Result<bool, GitError>;
+
+    fn get_hunk_headers(&self, filepath: &str) -> Result<Vec<HunkHeader>, GitError>;
+
+    fn discard_hunk(&self, filepath: &str, header: &HunkHeader) -> Result<(), GitError>;
+}
+
+impl Discard for Git {
+    /// restore the file from the index, or delete it when it is untracked
+    fn discard_file(&self, filepath: &str) -> Result<(), GitError> {
+        // an ignored entry has nothing to restore, `delete_untracked` refuses it
+        if get_entry_status(self, filepath)?.intersects(Status::WT_NEW | Status::IGNORED) {
+            return delete_untracked(self, filepath);
+        }
+        let mut checkout_builder = CheckoutBuilder::new();
+        checkout_builder.force().path(filepath);
+        self.repo.checkout_index(None, Some(&mut checkout_builder))
+    }
+
+    /// restore every tracked file from the index and delete the untracked ones
+    fn discard_all(&self) -> Result<(), GitError> {
+        let mut checkout_builder = CheckoutBuilder::new();
+        checkout_builder.force();
+        self.repo.checkout_index(None, Some(&mut checkout_builder))?;
+
+        let mut status_options = StatusOptions::new();
+        status_options.include_untracked(true);
+        let statuses = self.repo.statuses(Some(&mut status_options))?;
+        for entry in statuses.iter() {
+            if entry.status().contains(Status::WT_NEW) {
+                delete_untracked(self, entry.path().unwrap_or(""))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn is_untracked(&self, filepath: &str) -> Result<bool, GitError> {
+        let status = get_entry_status(self, filepath)?;
+        Ok(status.contains(Status::WT_NEW) && !status.contains(Status::IGNORED))
+    }
+
+    /// headers of the hunks between the index and the working tree
+    fn get_hunk_headers(&self, filepath: &str) -> Result<Vec<HunkHeader>, GitError> {
+        let mut diff_options = DiffOptions::new();
+        diff_options.pathspec(filepath).disable_pathspec_match(true);
+        let diff = self
+            .repo
+            .diff_index_to_workdir(None, Some(&mut diff_options))?;
+
+        let mut headers = Vec::new();
+        for delta_index in 0..diff.deltas().len() {
+            if let Some(patch) = Patch::from_diff(&diff, delta_index)? {
+                for hunk_index in 0..patch.num_hunks() {
+                    let (hunk, _nb_lines) = patch.hunk(hunk_index)?;
+                    headers.push(HunkHeader {
+                        old_start: hunk.old_start(),
+                        old_lines: hunk.old_lines(),
+                        new_start: hunk.new_start(),
+                        new_lines: hunk.new_lines(),
+                        text: String::from_utf8_lossy(hunk.header())
+                            .trim_end()
+                            .to_string(),
+                    });
+                }
+            }
+        }
+        Ok(headers)
+    }
+
+    /// revert the hunk at `header` by applying it in reverse. The diff is read again: when the
+    /// file changed since the hunk was confirmed and no hunk is at that place anymore, nothing
+    /// is discarded
+    fn discard_hunk(&self, filepath: &str, header: &HunkHeader) -> Result<(), GitError> {
+        let mut diff_options = DiffOptions::new();
+        diff_options
+            .pathspec(filepath)
+            .disable_pathspec_match(true)
+            .reverse(true);
+        let diff = self
+            .repo
+            .diff_index_to_workdir(None, Some(&mut diff_options))?;
+
+        // the reversed diff goes from the working tree to the index: old and new are swapped
+        let is_confirmed_hunk = |old_start, old_lines, new_start, new_lines| {
+            (old_start, old_lines, new_start, new_lines)
+                == (
+                    header.new_start,
+                    header.new_lines,
+                    header.old_start,
+                    header.old_lines,
+                )
+        };
+        let mut found = false;
+        for delta_index in 0..diff.deltas().len() {
+            if let Some(patch) = Patch::from_diff(&diff, delta_index)? {
+                for hunk_index in 0..patch.num_hunks() {
+                    let (hunk, _nb_lines) = patch.hunk(hunk_index)?;
+                    found |= is_confirmed_hunk(
+                        hunk.old_start(),
+                        hunk.old_lines(),
+                        hunk.new_start(),
+                        hunk.new_lines(),
+                    );
+                }
+            }
+        }
+        if !found {
+            return Err(GitError::from_str(&format!(
+                "{} changed, the hunk {} is not there anymore",
+                filepath, header
+            )));
+        }
+
+        let mut apply_options = ApplyOptions::new();
+        apply_options.hunk_callback(|hunk| {
+            hunk.is_some_and(|hunk| {
+                is_confirmed_hunk(
+                    hunk.old_start(),
+                    hunk.old_lines(),
+                    hunk.new_start(),
+                    hunk.new_lines(),
+                )
+            })
+        });
+        self.repo
+            .apply(&diff, ApplyLocation::WorkDir, Some(&mut apply_options))
+    }
+}
+
+/// status of a file, or of an untracked directory listed with a trailing '/', which
+/// `status_file` cannot look up
+fn get_entry_status(git: &Git, filepath: &str) -> Result<Status, GitError> {
+    if !filepath.ends_with('/') {
+        return git.repo.status_file(Path::new(filepath));
+    }
+    let mut status_options = StatusOptions::new();
+    status_options
+        .include_untracked(true)
+        .include_ignored(true)
+        .pathspec(filepath)
+        .disable_pathspec_match(true);
+    let statuses = git.repo.statuses(Some(&mut status_options))?;
+    let status = statuses
+        .iter()
+        .find(|entry| entry.path() == Some(filepath))
+        .map(|entry| entry.status())
+        .unwrap_or(Status::CURRENT);
+    Ok(status)
+}
+
+/// whether `path` is or holds a repository of its own, a nested clone or a worktree
+fn contains_git_dir(path: &Path) -> bool {
+    if path.join(".git").symlink_metadata().is_ok() {
+        return true;
+    }
+    let Ok(entries) = fs::read_dir(path) else {
+        return false;
+    };
+    entries.flatten().any(|entry| {
+        entry.file_type().is_ok_and(|file_type| file_type.is_dir())
+            && contains_git_dir(&entry.path())
+    })
+}
+
+/// delete an untracked file or directory, refusing ignored entries and nested repositories
+fn delete_untracked(git: &Git, filepath: &str) -> Result<(), GitError> {
+    let workdir = git
+        .repo
+        .workdir()
+        .ok_or_else(|| GitError::from_str("Repository has no working tree"))?;
+    let path = workdir.join(filepath);
+
+    let status = get_entry_status(git, filepath)?;
+    if status.contains(Status::IGNORED) || !status.contains(Status::WT_NEW) {
+        return Err(GitError::from_str(&format!(
+            "{} is not an untracked file, it is not deleted",
+            filepath
+        )));
+    }
+    let is_dir = path
+        .symlink_metadata()
+        .is_ok_and(|metadata| metadata.is_dir());
+    if is_dir && contains_git_dir(&path) {
+        return Err(GitError::from_str(&format!(
+            "{} holds a git repository, it is not deleted",
+            filepath
+        )));
+    }
+
+    let removed = if is_dir {
+        fs::remove_dir_all(&path)
+    } else {
+        fs::remove_file(&path)
+    };
+    removed.map_err(|e| GitError::from_str(&format!("Cannot delete {}: {}", filepath, e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use git2::{Repository, Signature};
+    use std::{env, path::PathBuf};
+
+    /// twenty numbered lines, with `changed` lines replaced in the working tree only
+    fn numbered(changed: &[usize]) -> String {
+        (1..=20)
+            .map(|n| {
+                if changed.contains(&n) {
+                    format!("changed {}\n", n)
+                } else {
+                    format!("line {}\n", n)
+                }
+            })
+            .collect()
+    }
+
+    fn init(name: &str) -> (PathBuf, Git) {
+        let workdir =
+            env::temp_dir().join(format!("git-ui-discard-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&workdir);
+        let repo = Repository::init(&workdir).unwrap();
+        fs::write(workdir.join("file"), numbered(&[])).unwrap();
+        fs::write(workdir.join(".gitignore"), "build/\n").unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new("file")).unwrap();
+        index.add_path(Path::new(".gitignore")).unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = Signature::now("test", "test@example.com").unwrap();
+        repo.commit(Some("HEAD"), &signature, &signature, "init", &tree, &[])
+            .unwrap();
+        drop(tree);
+        (workdir, Git::new(repo))
+    }
+
+    #[test]
+    fn discarding_a_hunk_keeps_the_others() {
+        let (workdir, git) = init("hunk");
+        fs::write(workdir.join("file"), numbered(&[2, 10, 18])).unwrap();
+        let headers = git.get_hunk_headers("file").unwrap();
+        assert_eq!(headers.len(), 3);
+
+        git.discard_hunk("file", &headers[1]).unwrap();
+        assert_eq!(
+            fs::read_to_string(workdir.join("file")).unwrap(),
+            numbered(&[2, 18])
+        );
+        // the second hunk is gone: confirming it again discards nothing
+        assert!(git.discard_hunk("file", &headers[1]).is_err());
+        assert_eq!(git.get_hunk_headers("file").unwrap().len(), 2);
+        fs::remove_dir_all(&workdir).unwrap();
+    }
+
+    #[test]
+    fn a_hunk_moved_by_a_new_edit_is_refused() {
+        let (workdir, git) = init("moved");
+        fs::write(workdir.join("file"), numbered(&[10])).unwrap();
+        let header = git.get_hunk_headers("file").unwrap().remove(0);
+
+        // an edit touching the hunk before it is confirmed changes its line counts
+        fs::write(workdir.join("file"), numbered(&[10, 11])).unwrap();
+        assert!(git.discard_hunk("file", &header).is_err());
+        assert_eq!(
+            fs::read_to_string(workdir.join("file")).unwrap(),
+            numbered(&[10, 11])
+        );
+        fs::remove_dir_all(&workdir).unwrap();
+    }
+
+    #[test]
+    fn ignored_directories_and_nested_repositories_are_kept() {
+        let (workdir, git) = init("untracked");
+        fs::create_dir_all(workdir.join("build")).unwrap();
+        fs::write(workdir.join("build/out"), "binary").unwrap();
+        fs::create_dir_all(workdir.join("vendor/lib")).unwrap();
+        Repository::init(workdir.join("vendor/lib")).unwrap();
+        fs::write(workdir.join("vendor/README"), "vendored").unwrap();
+        fs::create_dir_all(workdir.join("notes")).unwrap();
+        fs::write(workdir.join("notes/todo"), "todo").unwrap();
+
+        assert!(!git.is_untracked("build/").unwrap());
+        assert!(git.discard_file("build/").is_err());
+        assert!(workdir.join("build/out").exists());
+
+        assert!(git.is_untracked("vendor/").unwrap());
+        assert!(git.discard_file("vendor/").is_err());
+        assert!(workdir.join("vendor/lib/.git").exists());
+
+        assert!(git.is_untracked("notes/").unwrap());
+        git.discard_file("notes/").unwrap();
+        assert!(!workdir.join("notes").exists());
+        fs::remove_dir_all(&workdir).unwrap();
+    }
+}
@@ -1,10 +1,47 @@
-use git2::{Status, StatusOptions};
+use git2::{
+    Delta, DiffDelta, Repository, Status, StatusEntry, StatusOptions, SubmoduleIgnore,
+    SubmoduleStatus,
+};
+use std::{collections::HashMap, fs, path::Path, time::Instant};
+
+use crate::git::{Git, IgnoreRules};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileStatus {
+    Modified,
+    Deleted,
+    Renamed,
+    Typechange,
+    Added,
+    Untracked,
+    Conflicted,
+    Ignored,
+}
+
+impl FileStatus {
+    pub fn to_char(self) -> char {
+        match self {
+            FileStatus::Modified => 'm',
+            FileStatus::Deleted => 'd',
+            FileStatus::Renamed => 'r',
+            FileStatus::Typechange => 't',
+            FileStatus::Added => 'n',
+            FileStatus::Untracked => '?',
+            FileStatus::Conflicted => 'c',
+            FileStatus::Ignored => '!',
+        }
+    }
+}
 
-use crate::git::Git;
 #[derive(Debug, Clone)]
 pub struct GitFile {
     pub filename: String,
-    pub status: char,
+    pub status: FileStatus,
+    pub old_filename: Option<String>,
+    pub similarity: Option<u8>,
+    pub submodule: Option<String>,
+    pub ignore_rule: Option<String>,
+    pub nb_files: Option<usize>,
 }
 
 pub enum TypeStaged {
@@ -12,63 +49,316 @@ pub enum TypeStaged {
     Unstaged,
 }
 
-const VALID_STATUSES: &[char] = &['m', 'd', 'r', 'n'];
+/// the files of the Status lists and what is too slow to read at every frame about them,
+/// loaded again at each refresh
+pub struct StatusDetails {
+    /// readable state of every submodule that is not clean, by path
+    submodules: HashMap<String, String>,
+    /// similarity of every rename, by old and new path
+    similarities: HashMap<(String, String), u8>,
+    /// rule behind every ignored path, when they are shown
+    ignore_rules: HashMap<String, String>,
+    staged: Vec<GitFile>,
+    unstaged: Vec<GitFile>,
+    /// when the repository was read
+    pub loaded_at: Instant,
+}
+
+impl StatusDetails {
+    pub fn load(repo: &Repository, options: ListOptions) -> StatusDetails {
+        let mut details = StatusDetails {
+            submodules: get_submodule_states(repo),
+            similarities: HashMap::new(),
+            ignore_rules: HashMap::new(),
+            staged: Vec::new(),
+            unstaged: Vec::new(),
+            loaded_at: Instant::now(),
+        };
+        let path_of = |path: Option<&Path>| {
+            path.map(|path| path.to_string_lossy().to_string())
+                .unwrap_or_default()
+        };
+        let mut rules = IgnoreRules::load(repo);
+        let mut status_options = StatusOptions::new();
+        status_options
+            .include_untracked(true)
+            .include_ignored(options.show_ignored)
+            .recurse_untracked_dirs(options.recurse_untracked)
+            .renames_head_to_index(true)
+            .renames_index_to_workdir(true);
+        let Ok(statuses) = repo.statuses(Some(&mut status_options)) else {
+            return details;
+        };
+        for entry in statuses.iter() {
+            if entry.status().contains(Status::IGNORED)
+                && let (Some(rules), Some(path)) = (rules.as_mut(), entry.path())
+            {
+                details
+                    .ignore_rules
+                    .insert(path.to_string(), rules.get_rule(path));
+            }
+            let deltas = [entry.head_to_index(), entry.index_to_workdir()];
+            for delta in deltas.into_iter().flatten() {
+                if delta.status() != Delta::Renamed {
+                    continue;
+                }
+                let paths = (
+                    path_of(delta.old_file().path()),
+                    path_of(delta.new_file().path()),
+                );
+                if let Some(similarity) = get_similarity(repo, &delta) {
+                    details.similarities.insert(paths, similarity);
+                }
+            }
+        }
+
+        // the files, once every detail they show is known
+        for entry in statuses.iter() {
+            if let Some(file) = GitFile::new_staged(&entry, &details) {
+                details.staged.push(file);
+            }
+            if let Some(file) = GitFile::new_unstaged(&entry, &details) {
+                details.unstaged.push(file);
+            }
+        }
+        let untracked_dirs: Vec<&mut GitFile> = details
+            .unstaged
+            .iter_mut()
+            .filter(|file| file.status == FileStatus::Untracked && file.filename.ends_with('/'))
+            .collect();
+        if !untracked_dirs.is_empty() {
+            let _ = count_untracked_files(repo, untracked_dirs);
+        }
+        details
+    }
+
+    /// files of one of the Status lists, as read by the last refresh
+    pub fn get_files(&self, typeneeded: TypeStaged) -> &[GitFile] {
+        match typeneeded {
+            TypeStaged::Staged => &self.staged,
+            TypeStaged::Unstaged => &self.unstaged,
+        }
+    }
+}
+
+/// what the Status lists show beyond tracked changes and untracked files
+#[derive(Default, Clone, Copy)]
+pub struct ListOptions {
+    pub show_ignored: bool,
+    pub recurse_untracked: bool,
+}
 
 impl GitFile {
-    fn new_unstaged(filename: String, status: Status) -> GitFile {
+    fn new_unstaged(entry: &StatusEntry, details: &StatusDetails) -> Option<GitFile> {
+        let status = entry.status();
+        let status = match status {
+            s if s.contains(Status::CONFLICTED) => FileStatus::Conflicted,
+            s if s.contains(Status::WT_RENAMED) => FileStatus::Renamed,
+            s if s.contains(Status::WT_TYPECHANGE) => FileStatus::Typechange,
+            s if s.contains(Status::WT_MODIFIED) => FileStatus::Modified,
+            s if s.contains(Status::WT_DELETED) => FileStatus::Deleted,
+            s if s.contains(Status::WT_NEW) => FileStatus::Untracked,
+            s if s.contains(Status::IGNORED) => FileStatus::Ignored,
+            _ => return None, // if staged
+        };
+        Some(GitFile::from_delta(
+            entry,
+            entry.index_to_workdir(),
+            status,
+            details,
+        ))
+    }
+
+    fn new_staged(entry: &StatusEntry, details: &StatusDetails) -> Option<GitFile> {
+        let status = match entry.status() {
+            s if s.contains(Status::INDEX_RENAMED) => FileStatus::Renamed,
+            s if s.contains(Status::INDEX_TYPECHANGE) => FileStatus::Typechange,
+            s if s.contains(Status::INDEX_MODIFIED) => FileStatus::Modified,
+            s if s.contains(Status::INDEX_DELETED) => FileStatus::Deleted,
+            s if s.contains(Status::INDEX_NEW) => FileStatus::Added,
+            _ => return None, //if unstaged
+        };
+        Some(GitFile::from_delta(
+            entry,
+            entry.head_to_index(),
+            status,
+            details,
+        ))
+    }
+
+    fn from_delta(
+        entry: &StatusEntry,
+        delta: Option<DiffDelta>,
+        status: FileStatus,
+        details: &StatusDetails,
+    ) -> GitFile {
+        let path_of = |path: Option<&Path>| path.map(|path| path.to_string_lossy().to_string());
+        let filename = delta
+            .as_ref()
+            .and_then(|delta| path_of(delta.new_file().path()))
+            .unwrap_or_else(|| entry.path().unwrap_or("").to_string());
+
+        let old_filename = match (&delta, status) {
+            (Some(delta), FileStatus::Renamed) => path_of(delta.old_file().path()),
+            _ => None,
+        };
+        let similarity = old_filename.as_ref().and_then(|old| {
+            let paths = (old.clone(), filename.clone());
+            details.similarities.get(&paths).copied()
+        });
+
         GitFile {
+            submodule: details.submodules.get(&filename).cloned(),
+            ignore_rule: details.ignore_rules.get(&filename).cloned(),
             filename,
-            status: match status {
-                s if s.contains(Status::WT_MODIFIED) => 'm',
-                s if s.contains(Status::WT_DELETED) => 'd',
-                s if s.contains(Status::WT_TYPECHANGE) => 't',
-                s if s.contains(Status::WT_RENAMED) => 'r',
-                s if s.contains(Status::WT_NEW) => 'n',
-                _ => 'o', // if staged
-            },
+            status,
+            old_filename,
+            similarity,
+            nb_files: None,
         }
     }
 
-    fn new_staged(filename: String, status: Status) -> GitFile {
-        GitFile {
-            filename,
-            status: match status {
-                s if s.contains(Status::INDEX_MODIFIED) => 'm',
-                s if s.contains(Status::INDEX_DELETED) => 'd',
-                s if s.contains(Status::INDEX_TYPECHANGE) => 't',
-                s if s.contains(Status::INDEX_RENAMED) => 'r',
-                s if s.contains(Status::INDEX_NEW) => 'n',
-                _ => 'o', //if unstaged
-            },
+    /// every path touched by the change: a rename involves both the old and the new path
+    pub fn get_paths(&self) -> Vec<String> {
+        match &self.old_filename {
+            Some(old) => vec![self.filename.clone(), old.clone()],
+            None => vec![self.filename.clone()],
+        }
+    }
+
+    pub fn get_display_name(&self, name: &str) -> String {
+        let mut display = match (&self.old_filename, self.similarity) {
+            (Some(old), Some(similarity)) => format!("{} → {} ({}%)", old, name, similarity),
+            (Some(old), None) => format!("{} → {}", old, name),
+            _ => name.to_string(),
+        };
+        if let Some(submodule) = &self.submodule {
+            display.push_str(&format!(" [{}]", submodule));
+        }
+        if let Some(rule) = &self.ignore_rule {
+            display.push_str(&format!(" [{}]", rule));
         }
+        if let Some(nb_files) = self.nb_files {
+            display.push_str(&format!(" ({} files)", nb_files));
+        }
+        display
     }
 }
 
-pub fn get_files(git: &Git, typeneeded: TypeStaged) -> Result<Vec<GitFile>, git2::Error> {
+/// files of one of the Status lists, from the last refresh of `git.status_details`
+pub fn get_files(git: &Git, typeneeded: TypeStaged) -> Vec<GitFile> {
+    git.status_details.get_files(typeneeded).to_vec()
+}
+
+/// number of files `git restore` and `git clean` would touch, untracked directories counted
+/// file by file
+pub fn count_unstaged_files(repo: &Repository) -> Result<usize, git2::Error> {
     let mut status_options = StatusOptions::new();
-    status_options.include_untracked(true);
+    status_options
+        .include_untracked(true)
+        .recurse_untracked_dirs(true);
+    let statuses = repo.statuses(Some(&mut status_options))?;
+    let unstaged = Status::WT_NEW
+        | Status::WT_MODIFIED
+        | Status::WT_DELETED
+        | Status::WT_RENAMED
+        | Status::WT_TYPECHANGE
+        | Status::CONFLICTED;
+    Ok(statuses
+        .iter()
+        .filter(|entry| entry.status().intersects(unstaged))
+        .count())
+}
 
-    let statuses = git.repo.statuses(Some(&mut status_options))?;
-    let mut all_file = Vec::new();
+/// number of files `git add` would stage in each collapsed untracked directory,
+/// so a large generated directory stands out before it is staged
+fn count_untracked_files(repo: &Repository, dirs: Vec<&mut GitFile>) -> Result<(), git2::Error> {
+    let mut status_options = StatusOptions::new();
+    status_options
+        .include_untracked(true)
+        .recurse_untracked_dirs(true);
+    for dir in &dirs {
+        status_options.pathspec(&dir.filename);
+    }
 
-    for entry in statuses.iter() {
-        let status = entry.status();
-        let file_path = entry.path().unwrap_or("").to_string();
+    let statuses = repo.statuses(Some(&mut status_options))?;
+    for dir in dirs {
+        let nb_files = statuses
+            .iter()
+            .filter(|entry| entry.status().contains(Status::WT_NEW))
+            .filter(|entry| {
+                entry
+                    .path()
+                    .is_some_and(|path| path.starts_with(&dir.filename))
+            })
+            .count();
+        dir.nb_files = Some(nb_files);
+    }
+    Ok(())
+}
 
-        match typeneeded {
-            TypeStaged::Unstaged => {
-                let gitfile = GitFile::new_unstaged(file_path, status);
-                if VALID_STATUSES.contains(&gitfile.status) {
-                    all_file.push(gitfile)
-                }
-            }
-            TypeStaged::Staged => {
-                let gitfile = GitFile::new_staged(file_path, status);
-                if VALID_STATUSES.contains(&gitfile.status) {
-                    all_file.push(gitfile)
-                }
+fn get_submodule_states(repo: &Repository) -> HashMap<String, String> {
+    let mut states = HashMap::new();
+    let Ok(submodules) = repo.submodules() else {
+        return states;
+    };
+
+    for submodule in submodules {
+        let Some(name) = submodule.name() else {
+            continue;
+        };
+        let Ok(status) = repo.submodule_status(name, SubmoduleIgnore::Unspecified) else {
+            continue;
+        };
+        let state = match status {
+            s if s.contains(SubmoduleStatus::WD_UNINITIALIZED) => "uninitialized",
+            s if s.contains(SubmoduleStatus::WD_MODIFIED) => "new commits",
+            s if s.contains(SubmoduleStatus::WD_INDEX_MODIFIED)
+                || s.contains(SubmoduleStatus::WD_WD_MODIFIED) =>
+            {
+                "modified content"
             }
+            s if s.contains(SubmoduleStatus::WD_UNTRACKED) => "untracked content",
+            s if s.contains(SubmoduleStatus::INDEX_ADDED) => "added submodule",
+            _ => "submodule",
+        };
+        states.insert(
+            submodule.path().to_string_lossy().to_string(),
+            state.to_string(),
+        );
+    }
+    states
+}
+
+/// approximate similarity of a rename: the share of lines the two sides have in common,
+/// close to but not always the score git uses to detect the rename
+fn get_similarity(repo: &Repository, delta: &DiffDelta) -> Option<u8> {
+    let old_content = read_side(repo, delta.old_file().id(), delta.old_file().path())?;
+    let new_content = read_side(repo, delta.new_file().id(), delta.new_file().path())?;
+
+    let mut old_lines: HashMap<&[u8], usize> = HashMap::new();
+    for line in old_content.split(|byte| *byte == b'\n') {
+        *old_lines.entry(line).or_default() += 1;
+    }
+    let nb_old = old_content.split(|byte| *byte == b'\n').count();
+    let nb_new = new_content.split(|byte| *byte == b'\n').count();
+
+    let mut common = 0;
+    for line in new_content.split(|byte| *byte == b'\n') {
+        if let Some(count) = old_lines.get_mut(line)
+            && *count > 0
+        {
+            *count -= 1;
+            common += 1;
         }
     }
-    Ok(all_file)
+    Some((common * 200 / (nb_old + nb_new).max(1)) as u8)
+}
+
+/// blob content, or the working tree file when the side has no object yet
+fn read_side(repo: &Repository, id: git2::Oid, path: Option<&Path>) -> Option<Vec<u8>> {
+    if let Ok(blob) = repo.find_blob(id) {
+        return Some(blob.content().to_vec());
+    }
+    fs::read(repo.workdir()?.join(path?)).ok()
 }
@@ -1,5 +1,5 @@
 use crossterm::event::{KeyCode, KeyEvent};
-use git2::{Error as GitError, Repository};
+use git2::{Error as GitError, IndexAddOption, ObjectType, Repository, Signature};
 use ratatui::{
     layout::{Constraint, Flex, Layout, Rect},
     widgets::{Block, Clear, Paragraph},
@@ -8,41 +8,120 @@ use ratatui::{
 use std::{path::Path, sync::mpsc, thread};
 
 use crate::{
-    git::{execute_push, get_repository, Branch, Commit, PushMode},
-    popup::Popup,
+    config::Config,
+    git::{
+        execute_delete_remote_branch, execute_push, Branch, Commit, HeadState, ListOptions,
+        Operation, PushMode, StatusDetails,
+    },
+    popup::{Notification, Popup},
 };
 
 pub struct Git {
     pub repo: Repository,
     pub branch: Branch,
     pub commit_popup: Popup,
+    pub commit_author: Option<Signature<'static>>,
+    pub notification: Notification,
+    pub journal: Vec<Operation>,
     pub push_mode: PushMode,
     pub push_message: String,
     pub push_process: bool,
     pub rx_push: Option<mpsc::Receiver<String>>,
+    pub readonly: bool,
+    pub config: Config,
+    pub status_details: StatusDetails,
 }
 
 impl Git {
     pub fn new(repository: Repository) -> Self {
         Git {
             branch: Branch::new(&repository),
+            status_details: StatusDetails::load(&repository, ListOptions::default()),
             repo: repository,
             commit_popup: Popup::new(),
+            commit_author: None,
+            notification: Notification::new(),
+            journal: Vec::new(),
             push_mode: PushMode::Normal,
             push_message: String::from("Are you sure you want to push your work ?"),
             push_process: false,
             rx_push: None,
+            readonly: false,
+            config: Config::default(),
         }
     }
 
+    /// read again the files of the Status lists with their submodule states, rename
+    /// similarities and ignore rules
+    pub fn refresh_status_details(&mut self, options: ListOptions) {
+        self.status_details = StatusDetails::load(&self.repo, options);
+    }
+
+    /// true, with a notification, when the repository is opened read-only
+    pub fn refuse_if_readonly(&mut self) -> bool {
+        if self.readonly {
+            self.notification.show(
+                "Read-only",
+                vec!["This action is disabled in read-only mode".to_string()],
+            );
+        }
+        self.readonly
+    }
+
     pub fn add(&self, filepath: &str) -> Result<(), GitError> {
         let mut index = self.repo.index()?;
-        index.add_path(Path::new(filepath))?;
+        // untracked directories are listed as one "dir/" entry: add_path only takes files
+        if filepath.ends_with('/') {
+            index.add_all([filepath].iter(), IndexAddOption::DEFAULT, None)?;
+            index.write()?;
+            return Ok(());
+        }
+        let deleted = self
+            .repo
+            .workdir()
+            .is_some_and(|workdir| !workdir.join(filepath).exists());
+        if deleted {
+            index.remove_path(Path::new(filepath))?;
+        } else {
+            index.add_path(Path::new(filepath))?;
+        }
+        index.write()?;
+        Ok(())
+    }
+
+    /// stage every change of the working tree, deletions included
+    pub fn add_all(&self) -> Result<(), GitError> {
+        let mut index = self.repo.index()?;
+        index.add_all(["*"].iter(), IndexAddOption::DEFAULT, None)?;
+        index.update_all(["*"].iter(), None)?;
         index.write()?;
         Ok(())
     }
 
+    /// reset the whole index to HEAD, the working tree is left untouched
+    pub fn restore_all_staged(&self) -> Result<(), GitError> {
+        match self.repo.head() {
+            Ok(head) => {
+                let head_commit = head.peel(ObjectType::Commit)?;
+                self.repo.reset_default(Some(&head_commit), ["*"].iter())?;
+            }
+            Err(_) => {
+                // nothing committed yet: unstaging means emptying the index
+                let mut index = self.repo.index()?;
+                index.clear()?;
+                index.write()?;
+            }
+        }
+        Ok(())
+    }
+
     pub fn restore_staged(&self, filepath: &str) -> Result<(), GitError> {
+        // before the first commit, unstaging is taking the file out of the index
+        if self.branch.head == HeadState::Unborn {
+            let mut index = self.repo.index()?;
+            index.remove_path(Path::new(filepath))?;
+            return index.write();
+        }
         let head = self.repo.head()?;
         let head_commit = head.peel_to_commit()?;
         let head_tree = head_commit.tree()?;
@@ -80,7 +159,11 @@ impl Git {
     }
 
     pub fn draw_push(&self, frame: &mut Frame, content: Rect) {
-        let block = Block::bordered().title("Push");
+        let title = match self.push_mode {
+            PushMode::DeleteRemote => "Delete remote branch",
+            _ => "Push",
+        };
+        let block = Block::bordered().title(title);
         let text = Paragraph::new(self.push_message.clone())
             .centered()
             .block(block);
@@ -102,8 +185,18 @@ impl Git {
             KeyCode::Right => self.commit_popup.move_cursor_right(),
             KeyCode::Backspace => self.commit_popup.delete_char(),
             KeyCode::Enter => {
-                let _ = self.git_commit();
-                self.commit_popup.input = String::new();
+                match self.git_commit() {
+                    Ok(_oid) => {
+                        // one more commit ahead, or the birth of the branch on an unborn HEAD
+                        self.branch = Branch::new(&self.repo);
+                        self.commit_author = None;
+                        self.commit_popup.set_input("");
+                    }
+                    // keep the message so the commit can be retried
+                    Err(e) => self
+                        .notification
+                        .show("Commit failed", vec![e.message().to_string()]),
+                }
                 self.commit_popup.activated = false
             }
             _ => {}
@@ -111,6 +204,13 @@ impl Git {
     }
 
     pub fn push_key_event(&mut self, key_event: KeyEvent) {
+        if self.push_mode == PushMode::DeleteRemote {
+            // nothing to start, Esc or Enter only closes the progress
+            if matches!(key_event.code, KeyCode::Esc | KeyCode::Enter) {
+                self.push_mode = PushMode::Normal;
+            }
+            return;
+        }
         match key_event.code {
             KeyCode::Esc => {
                 self.push_mode = PushMode::Normal;
@@ -127,32 +227,71 @@ impl Git {
         if self.push_process {
             return;
         }
+        match self.branch.head {
+            HeadState::Detached => {
+                self.push_message = "❌ Push failed: HEAD is detached".to_string();
+                return;
+            }
+            HeadState::Unborn => {
+                self.push_message = "❌ Push failed: nothing committed yet".to_string();
+                return;
+            }
+            HeadState::OnBranch => {}
+        }
+        let branch = self.branch.current.clone();
+        let remote = self.config.push.remote.clone();
+        self.spawn_push("Push", move |repo, tx| {
+            execute_push(repo, branch, remote, tx)
+        });
+    }
+
+    /// delete "origin/feature" from its remote in the background, the push popup shows how it goes
+    pub fn delete_remote_branch(&mut self, remote_branch: &str) -> Result<(), GitError> {
+        if self.push_process {
+            return Err(GitError::from_str("A push is already running"));
+        }
+        let (remote_name, branch) = Branch::split_remote_branch(&self.repo, remote_branch)?;
+        self.push_mode = PushMode::DeleteRemote;
+        self.spawn_push("Delete", move |repo, tx| {
+            execute_delete_remote_branch(repo, remote_name, branch, tx)
+        });
+        Ok(())
+    }
+
+    /// run `job` in a thread, its progress and result end up in `push_message`
+    fn spawn_push<F>(&mut self, operation: &'static str, job: F)
+    where
+        F: FnOnce(Repository, mpsc::Sender<String>) -> Result<String, GitError> + Send + 'static,
+    {
         let (tx, rx) = mpsc::channel();
         self.rx_push = Some(rx);
         self.push_process = true;
         self.push_message = "🔄 Initializing push...".to_string();
 
-        let repo = match get_repository() {
+        // the push thread needs its own handle on the repository already opened
+        let repo = match Repository::open(self.repo.path()) {
             Ok(value) => value,
             Err(_e) => {
-                self.push_message = "❌ Push failed: Can't get actual repo".to_string();
+                self.push_message = format!("❌ {} failed: Can't get actual repo", operation);
+                self.push_process = false;
+                self.rx_push = None;
                 return;
             }
         };
-        let branch = self.branch.current.clone();
 
-        thread::spawn(move || match execute_push(repo, branch, tx.clone()) {
+        thread::spawn(move || match job(repo, tx.clone()) {
             Ok(value) => {
                 tx.send(value).unwrap();
             }
             Err(error) => {
-                tx.send(format!("❌ Push failed: {}", error.message()))
+                tx.send(format!("❌ {} failed: {}", operation, error.message()))
                     .unwrap();
             }
         });
     }
 
-    pub fn update_push_status(&mut self) {
+    /// true once the push or deletion running has just ended
+    pub fn update_push_status(&mut self) -> bool {
         if let Some(rx) = &self.rx_push {
             // Récupérer TOUS les messages disponibles
             match rx.try_recv() {
@@ -161,10 +300,14 @@ impl Git {
                     if message.starts_with("✅") || message.starts_with("❌") {
                         self.push_process = false;
                         self.rx_push = None;
+                        // the upstream moved: ahead / behind counts changed
+                        self.branch = Branch::new(&self.repo);
+                        return true;
                     }
                 }
                 Err(_e) => {}
             }
         }
+        false
     }
 }
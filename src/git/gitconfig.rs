@@ -0,0 +1,122 @@
+use git2::{Config as GitConfigFile, ConfigLevel, Error as GitError};
+use std::{env, path::PathBuf};
+
+use crate::git::Git;
+
+pub struct ConfigEntry {
+    pub name: String,
+    pub value: String,
+    pub level: ConfigLevel,
+    /// a level with a higher priority sets the same key
+    pub overridden_by: Option<ConfigLevel>,
+}
+
+/// levels shown by the Config page, lowest priority first
+pub const CONFIG_LEVELS: [ConfigLevel; 5] = [
+    ConfigLevel::System,
+    ConfigLevel::XDG,
+    ConfigLevel::Global,
+    ConfigLevel::Local,
+    ConfigLevel::Worktree,
+];
+
+pub fn get_level_name(level: ConfigLevel) -> &'static str {
+    match level {
+        ConfigLevel::ProgramData => "programdata",
+        ConfigLevel::System => "system",
+        ConfigLevel::XDG => "xdg",
+        ConfigLevel::Global => "global",
+        ConfigLevel::Local => "local",
+        ConfigLevel::Worktree => "worktree",
+        ConfigLevel::App => "app",
+        ConfigLevel::Highest => "highest",
+    }
+}
+
+fn get_priority(level: ConfigLevel) -> usize {
+    CONFIG_LEVELS
+        .iter()
+        .position(|known| *known == level)
+        .unwrap_or(CONFIG_LEVELS.len())
+}
+
+pub trait GitConfig {
+    fn get_config_entries(&self) -> Result<Vec<ConfigEntry>, GitError>;
+
+    fn get_config_path(&self, level: ConfigLevel) -> Option<PathBuf>;
+
+    fn set_config(&self, level: ConfigLevel, name: &str, value: &str) -> Result<(), GitError>;
+
+    fn remove_config(&self, level: ConfigLevel, name: &str) -> Result<(), GitError>;
+}
+
+impl GitConfig for Git {
+    /// every entry of every level, sorted by level then name
+    fn get_config_entries(&self) -> Result<Vec<ConfigEntry>, GitError> {
+        let config = self.repo.config()?;
+        let mut entries = Vec::new();
+        let mut iterator = config.entries(None)?;
+        while let Some(entry) = iterator.next() {
+            let entry = entry?;
+            entries.push(ConfigEntry {
+                name: entry.name().unwrap_or("").to_string(),
+                value: entry.value().unwrap_or("").to_string(),
+                level: entry.level(),
+                overridden_by: None,
+            });
+        }
+
+        // names are case insensitive, the value of the highest level wins
+        for i in 0..entries.len() {
+            let name = entries[i].name.to_lowercase();
+            let priority = get_priority(entries[i].level);
+            entries[i].overridden_by = entries
+                .iter()
+                .filter(|other| other.name.to_lowercase() == name)
+                .filter(|other| get_priority(other.level) > priority)
+                .max_by_key(|other| get_priority(other.level))
+                .map(|other| other.level);
+        }
+        entries.sort_by(|a, b| {
+            (get_priority(a.level), &a.name).cmp(&(get_priority(b.level), &b.name))
+        });
+        Ok(entries)
+    }
+
+    fn get_config_path(&self, level: ConfigLevel) -> Option<PathBuf> {
+        match level {
+            ConfigLevel::System => GitConfigFile::find_system().ok(),
+            ConfigLevel::XDG => GitConfigFile::find_xdg().ok(),
+            ConfigLevel::Global => GitConfigFile::find_global()
+                .ok()
+                .or_else(|| Some(PathBuf::from(env::var_os("HOME")?).join(".gitconfig"))),
+            ConfigLevel::Local => Some(self.repo.path().join("config")),
+            ConfigLevel::Worktree => Some(self.repo.path().join("config.worktree")),
+            _ => None,
+        }
+    }
+
+    fn set_config(&self, level: ConfigLevel, name: &str, value: &str) -> Result<(), GitError> {
+        open_level(self, level)?.set_str(name, value)
+    }
+
+    fn remove_config(&self, level: ConfigLevel, name: &str) -> Result<(), GitError> {
+        let mut config = open_level(self, level)?;
+        // keys set several times (remote.*.fetch...) need the multivar version
+        match config.remove(name) {
+            Ok(()) => Ok(()),
+            Err(_) => config.remove_multivar(name, ".*"),
+        }
+    }
+}
+
+/// the file of one level, created when it does not exist yet (first global setting)
+fn open_level(git: &Git, level: ConfigLevel) -> Result<GitConfigFile, GitError> {
+    if let Ok(config) = git.repo.config()?.open_level(level) {
+        return Ok(config);
+    }
+    let path = git
+        .get_config_path(level)
+        .ok_or_else(|| GitError::from_str("No file for this configuration level"))?;
+    GitConfigFile::open(&path)
+}
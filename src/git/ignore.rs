@@ -0,0 +1,387 @@
+use git2::{Error as GitError, Repository};
+use std::{
+    collections::HashMap,
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use crate::git::Git;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum IgnoreFile {
+    GitIgnore,
+    InfoExclude,
+    Global,
+}
+
+impl IgnoreFile {
+    pub fn get_name(self) -> &'static str {
+        match self {
+            IgnoreFile::GitIgnore => ".gitignore",
+            IgnoreFile::InfoExclude => ".git/info/exclude",
+            IgnoreFile::Global => "global excludes file",
+        }
+    }
+}
+
+pub trait Ignore {
+    fn append_ignore(&self, pattern: &str, target: IgnoreFile) -> Result<(), GitError>;
+}
+
+impl Ignore for Git {
+    /// add `pattern` on its own line at the end of the chosen ignore file
+    fn append_ignore(&self, pattern: &str, target: IgnoreFile) -> Result<(), GitError> {
+        let path = get_ignore_file_path(&self.repo, target)
+            .ok_or_else(|| GitError::from_str("Cannot locate the ignore file"))?;
+        let to_error = |e: std::io::Error| {
+            GitError::from_str(&format!("Cannot write {}: {}", path.display(), e))
+        };
+
+        let mut content = fs::read_to_string(&path).unwrap_or_default();
+        if content.lines().any(|line| line.trim() == pattern) {
+            return Ok(());
+        }
+        if !content.is_empty() && !content.ends_with('\n') {
+            content.push('\n');
+        }
+        content.push_str(pattern);
+        content.push('\n');
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(to_error)?;
+        }
+        fs::write(&path, content).map_err(to_error)
+    }
+}
+
+fn get_ignore_file_path(repo: &Repository, target: IgnoreFile) -> Option<PathBuf> {
+    match target {
+        IgnoreFile::GitIgnore => Some(repo.workdir()?.join(".gitignore")),
+        IgnoreFile::InfoExclude => Some(repo.path().join("info").join("exclude")),
+        IgnoreFile::Global => get_global_excludes_path(repo),
+    }
+}
+
+/// core.excludesFile, or the XDG default git falls back to
+fn get_global_excludes_path(repo: &Repository) -> Option<PathBuf> {
+    if let Ok(config) = repo.config()
+        && let Ok(path) = config.get_path("core.excludesFile")
+    {
+        return Some(path);
+    }
+    match env::var_os("XDG_CONFIG_HOME").filter(|dir| !dir.is_empty()) {
+        Some(dir) => Some(PathBuf::from(dir).join("git").join("ignore")),
+        None => Some(
+            PathBuf::from(env::var_os("HOME")?)
+                .join(".config")
+                .join("git")
+                .join("ignore"),
+        ),
+    }
+}
+
+struct Rule {
+    source: String,
+    line: usize,
+    text: String,
+    negated: bool,
+    dir_only: bool,
+    anchored: bool,
+    glob: Vec<char>,
+    // directory of the .gitignore holding the rule, relative to the workdir ("" or "dir/")
+    base: String,
+}
+
+impl Rule {
+    fn parse(source: &str, line: usize, text: &str, base: &str) -> Option<Rule> {
+        let trimmed = text.trim_end();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            return None;
+        }
+        let (negated, pattern) = match trimmed.strip_prefix('!') {
+            Some(pattern) => (true, pattern),
+            None => (false, trimmed),
+        };
+        let pattern = pattern.strip_prefix('\\').unwrap_or(pattern);
+        let (dir_only, pattern) = match pattern.strip_suffix('/') {
+            Some(pattern) => (true, pattern),
+            None => (false, pattern),
+        };
+        Some(Rule {
+            source: source.to_string(),
+            line,
+            text: trimmed.to_string(),
+            negated,
+            dir_only,
+            anchored: pattern.contains('/'),
+            glob: pattern.trim_start_matches('/').chars().collect(),
+            base: base.to_string(),
+        })
+    }
+
+    fn is_matching(&self, path: &str, is_dir: bool) -> bool {
+        if self.dir_only && !is_dir {
+            return false;
+        }
+        let Some(relative) = path.strip_prefix(&self.base) else {
+            return false;
+        };
+        let relative = if self.anchored {
+            relative
+        } else {
+            relative.rsplit('/').next().unwrap_or(relative)
+        };
+        let text: Vec<char> = relative.chars().collect();
+        glob_match(&self.glob, &text)
+    }
+}
+
+/// rules of the ignore files, read once per refresh to name the rule behind each
+/// file git reports as ignored
+pub struct IgnoreRules {
+    workdir: PathBuf,
+    /// lowest precedence first: global excludes, .git/info/exclude, the root .gitignore
+    root: Vec<Rule>,
+    /// rules of the .gitignore of each directory looked at so far, by path ("dir/")
+    nested: HashMap<String, Vec<Rule>>,
+}
+
+impl IgnoreRules {
+    pub fn load(repo: &Repository) -> Option<IgnoreRules> {
+        let workdir = repo.workdir()?.to_path_buf();
+        let mut root = Vec::new();
+        if let Some(path) = get_global_excludes_path(repo) {
+            read_rules(&path, &path.display().to_string(), "", &mut root);
+        }
+        read_rules(
+            &repo.path().join("info").join("exclude"),
+            ".git/info/exclude",
+            "",
+            &mut root,
+        );
+        read_rules(&workdir.join(".gitignore"), ".gitignore", "", &mut root);
+        Some(IgnoreRules {
+            workdir,
+            root,
+            nested: HashMap::new(),
+        })
+    }
+
+    /// "source:line: pattern" of the rule that ignores `filepath`, a path git reports as ignored
+    pub fn get_rule(&mut self, filepath: &str) -> String {
+        let components: Vec<&str> = filepath.trim_end_matches('/').split('/').collect();
+        let bases: Vec<String> = (1..components.len())
+            .map(|depth| format!("{}/", components[..depth].join("/")))
+            .collect();
+        for base in &bases {
+            if !self.nested.contains_key(base) {
+                let mut rules = Vec::new();
+                let source = format!("{}.gitignore", base);
+                read_rules(&self.workdir.join(&source), &source, base, &mut rules);
+                self.nested.insert(base.clone(), rules);
+            }
+        }
+
+        // a file is ignored by its own rule or by the one of an ignored parent directory
+        for depth in 1..=components.len() {
+            let candidate = components[..depth].join("/");
+            let is_dir = depth < components.len() || filepath.ends_with('/');
+            let nested = bases[..depth - 1]
+                .iter()
+                .filter_map(|base| self.nested.get(base))
+                .flatten();
+            let rule = self
+                .root
+                .iter()
+                .chain(nested)
+                .rev()
+                .find(|rule| rule.is_matching(&candidate, is_dir));
+            if let Some(rule) = rule
+                && !rule.negated
+            {
+                return format!("{}:{}: {}", rule.source, rule.line, rule.text);
+            }
+        }
+        // git also reports a directory as ignored when everything inside it is
+        if filepath.ends_with('/') {
+            "every file inside is ignored".to_string()
+        } else {
+            "ignored".to_string()
+        }
+    }
+}
+
+fn read_rules(path: &Path, source: &str, base: &str, rules: &mut Vec<Rule>) {
+    let Ok(content) = fs::read_to_string(path) else {
+        return;
+    };
+    rules.extend(
+        content
+            .lines()
+            .enumerate()
+            .filter_map(|(i, line)| Rule::parse(source, i + 1, line, base)),
+    );
+}
+
+/// gitignore flavoured glob: `*` and `?` stay inside a path component, `**` crosses them
+fn glob_match(pattern: &[char], text: &[char]) -> bool {
+    match pattern {
+        [] => text.is_empty(),
+        ['*', '*', '/', rest @ ..] => {
+            glob_match(rest, text)
+                || text
+                    .iter()
+                    .enumerate()
+                    .any(|(i, c)| *c == '/' && glob_match(rest, &text[i + 1..]))
+        }
+        ['*', '*', rest @ ..] => (0..=text.len()).any(|i| glob_match(rest, &text[i..])),
+        ['*', rest @ ..] => {
+            let end = text.iter().position(|c| *c == '/').unwrap_or(text.len());
+            (0..=end).any(|i| glob_match(rest, &text[i..]))
+        }
+        ['?', rest @ ..] => {
+            matches!(text.first(), Some(c) if *c != '/') && glob_match(rest, &text[1..])
+        }
+        ['[', rest @ ..] => match (text.first(), rest.iter().position(|c| *c == ']')) {
+            (Some(c), Some(end)) if end > 0 => {
+                *c != '/'
+                    && match_class(&rest[..end], *c)
+                    && glob_match(&rest[end + 1..], &text[1..])
+            }
+            _ => text.first() == Some(&'[') && glob_match(rest, &text[1..]),
+        },
+        ['\\', escaped, rest @ ..] => text.first() == Some(escaped) && glob_match(rest, &text[1..]),
+        [literal, rest @ ..] => text.first() == Some(literal) && glob_match(rest, &text[1..]),
+    }
+}
+
+/// `[abc]`, `[a-z]` and their negation `[!a-z]` / `[^a-z]`
+fn match_class(class: &[char], c: char) -> bool {
+    let (negated, class) = match class {
+        ['!' | '^', rest @ ..] => (true, rest),
+        _ => (false, class),
+    };
+    let mut found = false;
+    let mut i = 0;
+    while i < class.len() {
+        if i + 2 < class.len() && class[i + 1] == '-' {
+            found |= class[i] <= c && c <= class[i + 2];
+            i += 3;
+        } else {
+            found |= class[i] == c;
+            i += 1;
+        }
+    }
+    found != negated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn is_glob_matching(pattern: &str, text: &str) -> bool {
+        let pattern: Vec<char> = pattern.chars().collect();
+        let text: Vec<char> = text.chars().collect();
+        glob_match(&pattern, &text)
+    }
+
+    #[test]
+    fn star_stays_in_a_component() {
+        assert!(is_glob_matching("*.log", "debug.log"));
+        assert!(is_glob_matching("*", ""));
+        assert!(!is_glob_matching("*.log", "logs/debug.log"));
+        assert!(!is_glob_matching("*.log", "debug.txt"));
+    }
+
+    #[test]
+    fn double_star_crosses_components() {
+        assert!(is_glob_matching("**/build", "build"));
+        assert!(is_glob_matching("**/build", "a/b/build"));
+        assert!(is_glob_matching("doc/**", "doc/a/b.md"));
+        assert!(is_glob_matching("a/**/b", "a/b"));
+        assert!(is_glob_matching("a/**/b", "a/x/y/b"));
+        assert!(!is_glob_matching("a/**/b", "a/x/c"));
+    }
+
+    #[test]
+    fn question_mark_and_classes() {
+        assert!(is_glob_matching("file?.txt", "file1.txt"));
+        assert!(!is_glob_matching("file?.txt", "file/.txt"));
+        assert!(is_glob_matching("[abc].rs", "b.rs"));
+        assert!(is_glob_matching("[a-c].rs", "c.rs"));
+        assert!(!is_glob_matching("[a-c].rs", "d.rs"));
+        assert!(is_glob_matching("[!a-c].rs", "d.rs"));
+        assert!(is_glob_matching("[^a-c].rs", "d.rs"));
+        // an unclosed class is a literal bracket
+        assert!(is_glob_matching("[abc", "[abc"));
+    }
+
+    #[test]
+    fn escaped_characters_are_literal() {
+        assert!(is_glob_matching("\\*.txt", "*.txt"));
+        assert!(!is_glob_matching("\\*.txt", "a.txt"));
+    }
+
+    #[test]
+    fn parse_skips_blank_lines_and_comments() {
+        assert!(Rule::parse(".gitignore", 1, "", "").is_none());
+        assert!(Rule::parse(".gitignore", 1, "   ", "").is_none());
+        assert!(Rule::parse(".gitignore", 1, "# comment", "").is_none());
+    }
+
+    #[test]
+    fn parse_reads_the_pattern_flags() {
+        let rule = Rule::parse(".gitignore", 3, "!/target/  ", "").unwrap();
+        assert_eq!(rule.line, 3);
+        assert_eq!(rule.text, "!/target/");
+        assert!(rule.negated);
+        assert!(rule.dir_only);
+        assert!(rule.anchored);
+        assert_eq!(rule.glob, vec!['t', 'a', 'r', 'g', 'e', 't']);
+
+        let rule = Rule::parse(".gitignore", 1, "*.log", "").unwrap();
+        assert!(!rule.negated && !rule.dir_only && !rule.anchored);
+
+        // an escaped `!` starts a pattern instead of negating it
+        let rule = Rule::parse(".gitignore", 1, "\\!important", "").unwrap();
+        assert!(!rule.negated);
+        assert_eq!(rule.glob.iter().collect::<String>(), "!important");
+    }
+
+    #[test]
+    fn rules_match_relative_to_their_directory() {
+        let rule = Rule::parse("src/.gitignore", 1, "*.tmp", "src/").unwrap();
+        assert!(rule.is_matching("src/a/b.tmp", false));
+        assert!(!rule.is_matching("b.tmp", false));
+
+        let rule = Rule::parse(".gitignore", 1, "/build", "").unwrap();
+        assert!(rule.is_matching("build", true));
+        assert!(!rule.is_matching("src/build", true));
+
+        let rule = Rule::parse(".gitignore", 1, "cache/", "").unwrap();
+        assert!(rule.is_matching("a/cache", true));
+        assert!(!rule.is_matching("a/cache", false));
+    }
+
+    #[test]
+    fn get_rule_names_the_closest_rule() {
+        let workdir = env::temp_dir().join(format!("git-ui-ignore-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&workdir);
+        let repo = Repository::init(&workdir).unwrap();
+        fs::write(workdir.join(".gitignore"), "*.log\n/target/\n").unwrap();
+        fs::create_dir_all(workdir.join("src")).unwrap();
+        fs::write(
+            workdir.join("src/.gitignore"),
+            "# local\n!keep.log\n*.tmp\n",
+        )
+        .unwrap();
+
+        let mut rules = IgnoreRules::load(&repo).unwrap();
+        assert_eq!(rules.get_rule("debug.log"), ".gitignore:1: *.log");
+        assert_eq!(rules.get_rule("target/"), ".gitignore:2: /target/");
+        assert_eq!(rules.get_rule("target/debug/app"), ".gitignore:2: /target/");
+        assert_eq!(rules.get_rule("src/a.tmp"), "src/.gitignore:3: *.tmp");
+        assert_eq!(rules.get_rule("src/keep.log"), "ignored");
+        fs::remove_dir_all(&workdir).unwrap();
+    }
+}
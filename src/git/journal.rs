@@ -0,0 +1,307 @@
+use git2::{Error as GitError, Oid};
+
+use crate::git::{get_reflog, Branch, Git, ReflogEntry};
+
+/// start of the reflog messages written by an undo, so the next undo steps further back
+const UNDO_PREFIX: &str = "git-ui undo";
+
+/// how to put a branch back as it was, the reflog of HEAD does not record these
+pub enum Undo {
+    Delete(String),
+    Restore(String, Oid),
+    /// rename the branch named first back to the second name
+    Rename(String, String),
+}
+
+pub struct Operation {
+    pub description: String,
+    pub undo: Undo,
+    /// number of entries in the reflog of HEAD when the operation was done
+    pub head_log_len: usize,
+}
+
+pub trait Journal {
+    fn record(&mut self, description: String, undo: Undo);
+
+    fn undo_last(&mut self) -> Result<String, GitError>;
+}
+
+impl Journal for Git {
+    fn record(&mut self, description: String, undo: Undo) {
+        let head_log_len = self.repo.reflog("HEAD").map(|log| log.len()).unwrap_or(0);
+        self.journal.push(Operation {
+            description,
+            undo,
+            head_log_len,
+        });
+    }
+
+    /// revert whichever is the most recent of the last move of HEAD and the last
+    /// journaled operation, and return its description
+    fn undo_last(&mut self) -> Result<String, GitError> {
+        let entry = self.get_undoable_entry()?;
+        let journal_is_newer = match (&entry, self.journal.last()) {
+            (_, None) => false,
+            (None, Some(_)) => true,
+            (Some((position, _)), Some(operation)) => operation.head_log_len > *position,
+        };
+
+        let description = if journal_is_newer {
+            self.undo_operation()?
+        } else if let Some((_, entry)) = entry {
+            self.undo_reflog_entry(&entry)?;
+            entry.message
+        } else {
+            return Err(GitError::from_str("Nothing to undo"));
+        };
+        self.branch = Branch::new(&self.repo);
+        Ok(description)
+    }
+}
+
+impl Git {
+    /// the entry of the reflog of HEAD the next undo reverts, with its position counted
+    /// from the oldest entry. Every undo entry cancels the entry before it, so repeated
+    /// undos keep stepping back
+    fn get_undoable_entry(&self) -> Result<Option<(usize, ReflogEntry)>, GitError> {
+        let entries = get_reflog(&self.repo, "HEAD")?;
+        let len = entries.len();
+        let mut pending = 0;
+        for (i, entry) in entries.into_iter().enumerate() {
+            if entry.message.starts_with(UNDO_PREFIX) {
+                pending += 1;
+            } else if entry.old_id == entry.new_id && !entry.message.starts_with("checkout:") {
+                // HEAD did not move, e.g. the rename of the current branch
+                continue;
+            } else if pending > 0 {
+                pending -= 1;
+            } else {
+                return Ok(Some((len - 1 - i, entry)));
+            }
+        }
+        Ok(None)
+    }
+
+    fn undo_operation(&mut self) -> Result<String, GitError> {
+        let operation = self
+            .journal
+            .pop()
+            .ok_or_else(|| GitError::from_str("Nothing to undo"))?;
+
+        match self.apply_undo(&operation.undo) {
+            Ok(()) => Ok(operation.description),
+            Err(e) => {
+                // keep it so the user can retry once the blocking issue is fixed
+                self.journal.push(operation);
+                Err(e)
+            }
+        }
+    }
+
+    fn apply_undo(&mut self, undo: &Undo) -> Result<(), GitError> {
+        match undo {
+            Undo::Delete(branch_name) => self.branch.delete_branch(branch_name, &self.repo),
+            Undo::Restore(branch_name, id) => {
+                let commit = self.repo.find_commit(*id)?;
+                self.repo.branch(branch_name, &commit, true)?;
+                Ok(())
+            }
+            Undo::Rename(new_name, old_name) => {
+                self.branch.rename_branch(new_name, old_name, &self.repo)
+            }
+        }
+    }
+
+    /// put HEAD back where `entry` found it: a checkout switches back to the previous
+    /// branch, a commit leaves its changes staged, anything else (reset, merge, pull)
+    /// also brings back the files unless it would overwrite local changes
+    fn undo_reflog_entry(&mut self, entry: &ReflogEntry) -> Result<(), GitError> {
+        let message = format!("{}: {}", UNDO_PREFIX, entry.message);
+        if entry.old_id.is_zero() {
+            return Err(GitError::from_str(&format!(
+                "Nothing before \"{}\" to go back to",
+                entry.message
+            )));
+        }
+
+        if let Some(moves) = entry.message.strip_prefix("checkout: moving from ") {
+            let previous = moves.split(" to ").next().unwrap_or(moves);
+            let refname = format!("refs/heads/{}", previous);
+            // the previous branch may have moved since, go back to its tip like `git checkout -`
+            let branch_tip = self
+                .repo
+                .find_reference(&refname)
+                .ok()
+                .and_then(|reference| reference.target());
+            match branch_tip {
+                Some(tip) => {
+                    Branch::checkout_tree_safely(tip, &self.repo)?;
+                    self.repo
+                        .reference_symbolic("HEAD", &refname, true, &message)?;
+                }
+                None => {
+                    Branch::checkout_tree_safely(entry.old_id, &self.repo)?;
+                    self.repo.reference("HEAD", entry.old_id, true, &message)?;
+                }
+            }
+            return Ok(());
+        }
+
+        if !entry.message.starts_with("commit") {
+            Branch::checkout_tree_safely(entry.old_id, &self.repo)?;
+        }
+        // moves the checked out branch, or HEAD itself when detached
+        self.repo.head()?.set_target(entry.old_id, &message)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::{get_short_id, RestoreRef};
+    use git2::{Repository, Signature};
+    use std::{env, fs, path::Path};
+
+    struct TempRepo {
+        git: Git,
+    }
+
+    impl TempRepo {
+        fn new(name: &str) -> Self {
+            let workdir =
+                env::temp_dir().join(format!("git-ui-journal-{}-{}", name, std::process::id()));
+            let _ = fs::remove_dir_all(&workdir);
+            let repo = Repository::init(&workdir).unwrap();
+            repo.set_head("refs/heads/main").unwrap();
+            let mut temp = TempRepo {
+                git: Git::new(repo),
+            };
+            temp.commit("first");
+            temp
+        }
+
+        fn workdir(&self) -> &Path {
+            self.git.repo.workdir().unwrap()
+        }
+
+        /// write `summary` into `file` and commit it on HEAD, the reflog says "commit: <summary>"
+        fn commit(&mut self, summary: &str) -> Oid {
+            let repo = &self.git.repo;
+            fs::write(self.workdir().join("file"), summary).unwrap();
+            let mut index = repo.index().unwrap();
+            index.add_path(Path::new("file")).unwrap();
+            index.write().unwrap();
+            let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+            let signature = Signature::now("test", "test@example.com").unwrap();
+            let parent = repo.head().ok().and_then(|head| head.peel_to_commit().ok());
+            let parents: Vec<_> = parent.iter().collect();
+            let id = repo
+                .commit(
+                    Some("HEAD"),
+                    &signature,
+                    &signature,
+                    summary,
+                    &tree,
+                    &parents,
+                )
+                .unwrap();
+            self.git.branch = Branch::new(&self.git.repo);
+            id
+        }
+
+        fn head(&self) -> Oid {
+            self.git.repo.head().unwrap().target().unwrap()
+        }
+    }
+
+    impl Drop for TempRepo {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(self.workdir());
+        }
+    }
+
+    #[test]
+    fn undo_of_a_commit_keeps_its_changes_staged() {
+        let mut temp = TempRepo::new("commit");
+        let first = temp.head();
+        temp.commit("second");
+
+        assert_eq!(temp.git.undo_last().unwrap(), "commit: second");
+        assert_eq!(temp.head(), first);
+        assert_eq!(
+            fs::read_to_string(temp.workdir().join("file")).unwrap(),
+            "second"
+        );
+        let status = temp.git.repo.status_file(Path::new("file")).unwrap();
+        assert!(status.contains(git2::Status::INDEX_MODIFIED));
+    }
+
+    #[test]
+    fn repeated_undos_step_further_back() {
+        let mut temp = TempRepo::new("repeat");
+        let first = temp.head();
+        let second = temp.commit("second");
+        temp.commit("third");
+
+        temp.git.undo_last().unwrap();
+        assert_eq!(temp.head(), second);
+        // the undo entry cancels "commit: third", the next undo reverts "commit: second"
+        assert_eq!(temp.git.undo_last().unwrap(), "commit: second");
+        assert_eq!(temp.head(), first);
+        assert!(temp.git.undo_last().is_err());
+    }
+
+    #[test]
+    fn journaled_operation_newer_than_head_is_undone_first() {
+        let mut temp = TempRepo::new("journal");
+        let second = temp.commit("second");
+        let head = temp.git.repo.find_commit(second).unwrap();
+        temp.git.repo.branch("feature", &head, false).unwrap();
+        drop(head);
+        temp.git.record(
+            "create feature".to_string(),
+            Undo::Delete("feature".to_string()),
+        );
+
+        assert_eq!(temp.git.undo_last().unwrap(), "create feature");
+        assert!(temp.git.journal.is_empty());
+        assert!(temp
+            .git
+            .repo
+            .find_branch("feature", git2::BranchType::Local)
+            .is_err());
+        assert_eq!(temp.head(), second);
+
+        // the journal is empty: the commit before it is next
+        assert_eq!(temp.git.undo_last().unwrap(), "commit: second");
+    }
+
+    #[test]
+    fn restored_branch_is_journaled_with_its_short_id() {
+        let mut temp = TempRepo::new("restore");
+        let first = temp.head();
+        let second = temp.commit("second");
+        let head = temp.git.repo.find_commit(second).unwrap();
+        temp.git.repo.branch("feature", &head, false).unwrap();
+        drop(head);
+
+        temp.git.restore_ref("refs/heads/feature", first).unwrap();
+        let description = format!("restore feature to {}", get_short_id(first));
+        assert_eq!(temp.git.journal[0].description, description);
+        assert_eq!(temp.git.undo_last().unwrap(), description);
+        let feature = temp.git.repo.refname_to_id("refs/heads/feature").unwrap();
+        assert_eq!(feature, second);
+    }
+
+    #[test]
+    fn failed_journal_undo_is_kept_for_a_retry() {
+        let mut temp = TempRepo::new("retry");
+        temp.git.record(
+            "rename gone to renamed".to_string(),
+            Undo::Rename("gone".to_string(), "renamed".to_string()),
+        );
+        assert!(temp.git.undo_last().is_err());
+        assert_eq!(temp.git.journal.len(), 1);
+    }
+}
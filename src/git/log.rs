@@ -0,0 +1,70 @@
+use git2::{Error as GitError, Oid, Repository, Sort};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[derive(Debug, Clone)]
+pub struct CommitInfo {
+    pub id: Oid,
+    pub short_id: String,
+    pub summary: String,
+    pub message: String,
+    pub author: String,
+    pub time: i64,
+}
+
+impl CommitInfo {
+    pub fn from_commit(commit: &git2::Commit) -> CommitInfo {
+        let id = commit.id();
+        CommitInfo {
+            id,
+            short_id: get_short_id(id),
+            summary: commit.summary().unwrap_or("").to_string(),
+            message: commit.message().unwrap_or("").to_string(),
+            author: commit.author().name().unwrap_or("unknown").to_string(),
+            time: commit.time().seconds(),
+        }
+    }
+}
+
+/// abbreviated id shown wherever a commit is named
+pub fn get_short_id(id: Oid) -> String {
+    id.to_string()[..7].to_string()
+}
+
+/// list the commits reachable from `start` (a branch name, a ref or "HEAD"), newest first
+pub fn get_commits(
+    repo: &Repository,
+    start: &str,
+    limit: usize,
+) -> Result<Vec<CommitInfo>, GitError> {
+    let start_commit = repo.revparse_single(start)?.peel_to_commit()?;
+
+    let mut revwalk = repo.revwalk()?;
+    revwalk.set_sorting(Sort::TOPOLOGICAL | Sort::TIME)?;
+    revwalk.push(start_commit.id())?;
+
+    let mut commits = Vec::new();
+    for oid in revwalk.take(limit) {
+        let commit = repo.find_commit(oid?)?;
+        commits.push(CommitInfo::from_commit(&commit));
+    }
+    Ok(commits)
+}
+
+/// "3 days ago" style age of a commit made at `time` (seconds since the epoch)
+pub fn get_relative_time(time: i64) -> String {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs() as i64)
+        .unwrap_or(time);
+    let elapsed = (now - time).max(0);
+    let (value, unit) = match elapsed {
+        0..60 => return "just now".to_string(),
+        60..3600 => (elapsed / 60, "minute"),
+        3600..86400 => (elapsed / 3600, "hour"),
+        86400..2_592_000 => (elapsed / 86400, "day"),
+        2_592_000..31_536_000 => (elapsed / 2_592_000, "month"),
+        _ => (elapsed / 31_536_000, "year"),
+    };
+    let plural = if value > 1 { "s" } else { "" };
+    format!("{} {}{} ago", value, unit, plural)
+}
@@ -1,16 +1,39 @@
 mod branch;
+mod cherrypick;
 mod commit;
 mod diff;
+mod discard;
 mod get_repo;
 mod getstatus;
+mod gitconfig;
+mod ignore;
+mod journal;
+mod log;
 mod push;
+mod reflog;
+mod reset;
+mod stash;
 
+#[allow(clippy::module_inception)]
 mod git;
 
-pub use branch::Branch;
+pub use branch::{
+    Branch, BranchInfo, CheckoutMode, CheckoutOutcome, CheckoutTarget, HeadState,
+};
+pub use cherrypick::{get_conflicted_paths, CherryPick};
 pub use commit::Commit;
 pub use diff::get_file_diff;
+pub use discard::{Discard, HunkHeader};
 pub use get_repo::get_repository;
-pub use getstatus::{get_files, GitFile, TypeStaged};
+pub use getstatus::{
+    count_unstaged_files, get_files, FileStatus, GitFile, ListOptions, StatusDetails, TypeStaged,
+};
 pub use git::Git;
-pub use push::{execute_push, PushMode};
+pub use gitconfig::{get_level_name, ConfigEntry, GitConfig, CONFIG_LEVELS};
+pub use ignore::{Ignore, IgnoreFile, IgnoreRules};
+pub use journal::{Journal, Operation, Undo};
+pub use log::{get_commits, get_relative_time, get_short_id, CommitInfo};
+pub use push::{execute_delete_remote_branch, execute_push, PushMode};
+pub use reflog::{get_reflog, get_reflog_refs, ReflogEntry, RestoreRef};
+pub use reset::Reset;
+pub use stash::{apply_stash, Stash, StashEntry};
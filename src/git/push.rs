@@ -1,9 +1,11 @@
-use git2::{Cred, Error as GitError, PushOptions, RemoteCallbacks, Repository};
+use git2::{Cred, Error as GitError, PushOptions, Remote, RemoteCallbacks, Repository};
 use std::{path::Path, sync::mpsc::Sender};
 
 #[derive(PartialEq)]
 pub enum PushMode {
     Push,
+    /// progress of the deletion of a remote branch
+    DeleteRemote,
     Normal,
 }
 
@@ -12,6 +14,7 @@ const REMOTE_NAMES: [&str; 3] = ["origin", "upstream", "master"];
 pub fn execute_push(
     repo: Repository,
     branch: String,
+    remote_name: Option<String>,
     tx: Sender<String>,
 ) -> Result<String, GitError> {
     // 1.  verifier les prerequis d'un push
@@ -19,37 +22,72 @@ pub fn execute_push(
 
     {
         // 2. get remote
-        let mut remote = get_available_remote(&repo)?;
-
-        // 3. handle credentials
-        let mut callbacks = RemoteCallbacks::new();
-        setup_authentication_callbacks(&mut callbacks);
-
-        callbacks.push_transfer_progress(|current, total, _bytes| {
-            if total > 0 {
-                let percentage = (current * 100) / total;
-                tx.send(format!(
-                    "Push progress: {}% ({}/{})",
-                    percentage, current, total
-                ))
-                .unwrap();
-            }
-        });
-        // 4. Configurer les options de push
-        let mut push_options = PushOptions::new();
-        push_options.remote_callbacks(callbacks);
+        let mut remote = match &remote_name {
+            Some(name) => repo.find_remote(name)?,
+            None => get_available_remote(&repo)?,
+        };
 
-        // 5. Définir la refspec pour le push
+        // 3. Définir la refspec pour le push
         let refspec = format!("refs/heads/{}:refs/heads/{}", branch, branch);
-        let refspecs = &[refspec.as_str()];
 
-        // 6. Exécuter le push
-        remote.push(refspecs, Some(&mut push_options))?;
+        // 4. Exécuter le push
+        push_refspec(&mut remote, &refspec, &tx)?;
     }
-    // 7. Successfully push
+    // 5. Successfully push
     Ok(format!("✅ Successfully pushed branch '{}'", branch))
 }
 
+/// delete `branch` from `remote_name` by pushing nothing to it, then drop its remote-tracking ref
+pub fn execute_delete_remote_branch(
+    repo: Repository,
+    remote_name: String,
+    branch: String,
+    tx: Sender<String>,
+) -> Result<String, GitError> {
+    {
+        let mut remote = repo.find_remote(&remote_name)?;
+        push_refspec(&mut remote, &format!(":refs/heads/{}", branch), &tx)?;
+    }
+    // the push may already have pruned it
+    if let Ok(mut tracking) =
+        repo.find_reference(&format!("refs/remotes/{}/{}", remote_name, branch))
+    {
+        tracking.delete()?;
+    }
+    Ok(format!(
+        "✅ Deleted branch '{}' from {}",
+        branch, remote_name
+    ))
+}
+
+/// push `refspec` with the credentials of the user, reporting the progress on `tx`
+fn push_refspec(remote: &mut Remote, refspec: &str, tx: &Sender<String>) -> Result<(), GitError> {
+    let mut callbacks = RemoteCallbacks::new();
+    setup_authentication_callbacks(&mut callbacks);
+
+    callbacks.push_transfer_progress(|current, total, _bytes| {
+        if let Some(percentage) = (current * 100).checked_div(total) {
+            tx.send(format!(
+                "Push progress: {}% ({}/{})",
+                percentage, current, total
+            ))
+            .unwrap();
+        }
+    });
+    // a ref refused by the server (protected branch, hook) does not fail the push itself
+    callbacks.push_update_reference(|refname, status| match status {
+        Some(message) => Err(GitError::from_str(&format!(
+            "{} rejected: {}",
+            refname, message
+        ))),
+        None => Ok(()),
+    });
+    let mut push_options = PushOptions::new();
+    push_options.remote_callbacks(callbacks);
+
+    remote.push(&[refspec], Some(&mut push_options))
+}
+
 fn check_push_prerequisites(repo: &Repository, branch: String) -> Result<(), GitError> {
     // Vérifier que le repo n'est pas bare
     if repo.is_bare() {
@@ -76,7 +114,7 @@ fn check_push_prerequisites(repo: &Repository, branch: String) -> Result<(), Git
     Ok(())
 }
 
-fn get_available_remote(repo: &Repository) -> Result<git2::Remote, GitError> {
+fn get_available_remote(repo: &Repository) -> Result<git2::Remote<'_>, GitError> {
     let remotes = repo.remotes()?;
 
     if remotes.is_empty() {
@@ -86,10 +124,10 @@ fn get_available_remote(repo: &Repository) -> Result<git2::Remote, GitError> {
     }
 
     for remote_name in &REMOTE_NAMES {
-        if let Ok(remote) = repo.find_remote(remote_name) {
-            if remote.url().is_some() {
-                return Ok(remote);
-            }
+        if let Ok(remote) = repo.find_remote(remote_name)
+            && remote.url().is_some()
+        {
+            return Ok(remote);
         }
     }
 
@@ -0,0 +1,65 @@
+use git2::{Error as GitError, Oid, Repository, ResetType};
+
+use crate::git::{get_short_id, Git, Journal, Reset, Undo};
+
+#[derive(Debug, Clone)]
+pub struct ReflogEntry {
+    pub old_id: Oid,
+    pub new_id: Oid,
+    pub message: String,
+    pub committer: String,
+}
+
+/// refs with a reflog worth browsing: HEAD then every local branch
+pub fn get_reflog_refs(repo: &Repository) -> Result<Vec<String>, GitError> {
+    let mut refs = vec!["HEAD".to_string()];
+    for reference in repo.references_glob("refs/heads/*")? {
+        if let Some(name) = reference?.name() {
+            refs.push(name.to_string());
+        }
+    }
+    Ok(refs)
+}
+
+/// entries of the reflog of `refname`, newest first
+pub fn get_reflog(repo: &Repository, refname: &str) -> Result<Vec<ReflogEntry>, GitError> {
+    let reflog = repo.reflog(refname)?;
+    Ok(reflog
+        .iter()
+        .map(|entry| ReflogEntry {
+            old_id: entry.id_old(),
+            new_id: entry.id_new(),
+            message: entry.message().unwrap_or("").to_string(),
+            committer: entry.committer().name().unwrap_or("unknown").to_string(),
+        })
+        .collect())
+}
+
+pub trait RestoreRef {
+    fn restore_ref(&mut self, refname: &str, id: Oid) -> Result<(), GitError>;
+}
+
+impl RestoreRef for Git {
+    /// point `refname` back to `id`. HEAD and the checked out branch are hard reset
+    fn restore_ref(&mut self, refname: &str, id: Oid) -> Result<(), GitError> {
+        let head_name = self.repo.head()?.name().map(|name| name.to_string());
+        if refname == "HEAD" || head_name.as_deref() == Some(refname) {
+            return self.reset_head(id, ResetType::Hard);
+        }
+
+        let previous = self
+            .repo
+            .find_reference(refname)?
+            .target()
+            .ok_or_else(|| GitError::from_str("Symbolic references cannot be restored"))?;
+        self.repo
+            .reference(refname, id, true, "git-ui: restore from reflog")?;
+
+        let branch_name = refname.strip_prefix("refs/heads/").unwrap_or(refname);
+        self.record(
+            format!("restore {} to {}", branch_name, get_short_id(id)),
+            Undo::Restore(branch_name.to_string(), previous),
+        );
+        Ok(())
+    }
+}
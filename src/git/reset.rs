@@ -0,0 +1,156 @@
+use git2::{Error as GitError, Oid, ResetType, Status, StatusOptions};
+
+use crate::git::{get_short_id, Git};
+
+const MAX_PREVIEW_LINES: usize = 10;
+
+pub trait Reset {
+    fn reset_head(&mut self, id: Oid, kind: ResetType) -> Result<(), GitError>;
+
+    fn get_reset_preview(&self, id: Oid, kind: ResetType) -> Result<Vec<String>, GitError>;
+}
+
+impl Reset for Git {
+    /// move HEAD to `id`, the reflog of HEAD keeps where it was so the reset can be undone
+    fn reset_head(&mut self, id: Oid, kind: ResetType) -> Result<(), GitError> {
+        let target = self.repo.find_object(id, None)?;
+        self.repo.reset(&target, kind, None)
+    }
+
+    /// describe what a reset to `id` would drop: commits leaving the branch and,
+    /// for a hard reset, the uncommitted changes
+    fn get_reset_preview(&self, id: Oid, kind: ResetType) -> Result<Vec<String>, GitError> {
+        let head = self.repo.head()?.peel_to_commit()?.id();
+        let mut lines = Vec::new();
+
+        let mut revwalk = self.repo.revwalk()?;
+        revwalk.push(head)?;
+        revwalk.hide(id)?;
+        let lost_commits: Vec<Oid> = revwalk.collect::<Result<_, _>>()?;
+        lines.push(format!(
+            "{} commit(s) will leave the branch",
+            lost_commits.len()
+        ));
+        for oid in lost_commits.iter().take(MAX_PREVIEW_LINES) {
+            let commit = self.repo.find_commit(*oid)?;
+            lines.push(format!(
+                "  {} {}",
+                get_short_id(*oid),
+                commit.summary().unwrap_or("")
+            ));
+        }
+
+        if kind == ResetType::Hard {
+            let mut status_options = StatusOptions::new();
+            status_options.include_untracked(false);
+            let statuses = self.repo.statuses(Some(&mut status_options))?;
+            let lost_files: Vec<String> = statuses
+                .iter()
+                .filter(|entry| entry.status() != Status::CURRENT)
+                .map(|entry| entry.path().unwrap_or("").to_string())
+                .collect();
+
+            lines.push(format!(
+                "{} file(s) with uncommitted changes will be lost",
+                lost_files.len()
+            ));
+            for file in lost_files.iter().take(MAX_PREVIEW_LINES) {
+                lines.push(format!("  {}", file));
+            }
+        }
+        Ok(lines)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use git2::{Repository, Signature};
+    use std::{env, fs, path::Path};
+
+    /// three commits "one", "two", "three" changing `file`, HEAD on "three"
+    fn three_commits(workdir: &Path) -> (Git, Vec<Oid>) {
+        let _ = fs::remove_dir_all(workdir);
+        let repo = Repository::init(workdir).unwrap();
+        let signature = Signature::now("test", "test@example.com").unwrap();
+        let mut ids: Vec<Oid> = Vec::new();
+        for summary in ["one", "two", "three"] {
+            fs::write(workdir.join("file"), summary).unwrap();
+            let mut index = repo.index().unwrap();
+            index.add_path(Path::new("file")).unwrap();
+            index.write().unwrap();
+            let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+            let parent = ids.last().map(|id| repo.find_commit(*id).unwrap());
+            let parents: Vec<_> = parent.iter().collect();
+            let id = repo
+                .commit(
+                    Some("HEAD"),
+                    &signature,
+                    &signature,
+                    summary,
+                    &tree,
+                    &parents,
+                )
+                .unwrap();
+            ids.push(id);
+        }
+        (Git::new(repo), ids)
+    }
+
+    #[test]
+    fn preview_lists_the_commits_leaving_the_branch() {
+        let workdir = env::temp_dir().join(format!("git-ui-reset-preview-{}", std::process::id()));
+        let (git, ids) = three_commits(&workdir);
+
+        let preview = git.get_reset_preview(ids[0], ResetType::Soft).unwrap();
+        assert_eq!(
+            preview,
+            vec![
+                "2 commit(s) will leave the branch".to_string(),
+                format!("  {} three", get_short_id(ids[2])),
+                format!("  {} two", get_short_id(ids[1])),
+            ]
+        );
+
+        // a hard reset also names the files whose changes are dropped
+        fs::write(workdir.join("file"), "uncommitted").unwrap();
+        let preview = git.get_reset_preview(ids[1], ResetType::Hard).unwrap();
+        assert_eq!(preview.len(), 4);
+        assert_eq!(
+            preview[2],
+            "1 file(s) with uncommitted changes will be lost"
+        );
+        assert_eq!(preview[3], "  file");
+        fs::remove_dir_all(&workdir).unwrap();
+    }
+
+    #[test]
+    fn reset_kinds_move_head_index_and_worktree() {
+        let workdir = env::temp_dir().join(format!("git-ui-reset-kinds-{}", std::process::id()));
+        let (mut git, ids) = three_commits(&workdir);
+        let head = |git: &Git| git.repo.head().unwrap().target().unwrap();
+        let staged = |git: &Git| {
+            let index = git.repo.index().unwrap();
+            let entry = index.get_path(Path::new("file"), 0).unwrap();
+            let blob = git.repo.find_blob(entry.id).unwrap();
+            String::from_utf8_lossy(blob.content()).to_string()
+        };
+
+        git.reset_head(ids[1], ResetType::Soft).unwrap();
+        assert_eq!(head(&git), ids[1]);
+        assert_eq!(staged(&git), "three");
+
+        git.reset_head(ids[0], ResetType::Mixed).unwrap();
+        assert_eq!(head(&git), ids[0]);
+        assert_eq!(staged(&git), "one");
+        assert_eq!(fs::read_to_string(workdir.join("file")).unwrap(), "three");
+
+        git.reset_head(ids[2], ResetType::Hard).unwrap();
+        assert_eq!(head(&git), ids[2]);
+        assert_eq!(fs::read_to_string(workdir.join("file")).unwrap(), "three");
+        fs::write(workdir.join("file"), "dropped").unwrap();
+        git.reset_head(ids[1], ResetType::Hard).unwrap();
+        assert_eq!(fs::read_to_string(workdir.join("file")).unwrap(), "two");
+        fs::remove_dir_all(&workdir).unwrap();
+    }
+}
@@ -0,0 +1,256 @@
+use git2::{
+    build::CheckoutBuilder, CheckoutNotificationType, Delta, Error as GitError, Oid, Repository,
+    StashApplyOptions,
+};
+
+use crate::git::{get_conflicted_paths, FileStatus, Git};
+
+#[derive(Clone)]
+pub struct StashEntry {
+    /// n of stash@{n}
+    pub index: usize,
+    pub message: String,
+    pub id: Oid,
+}
+
+pub trait Stash {
+    fn get_stashes(&mut self) -> Result<Vec<StashEntry>, GitError>;
+
+    fn get_stash_files(&self, id: Oid) -> Result<Vec<(FileStatus, String)>, GitError>;
+
+    fn apply_stash(&mut self, entry: &StashEntry, pop: bool) -> Result<Vec<String>, GitError>;
+
+    fn drop_stash(&mut self, entry: &StashEntry) -> Result<(), GitError>;
+}
+
+impl Stash for Git {
+    /// stashes, newest first
+    fn get_stashes(&mut self) -> Result<Vec<StashEntry>, GitError> {
+        let mut stashes = Vec::new();
+        self.repo.stash_foreach(|index, message, id| {
+            stashes.push(StashEntry {
+                index,
+                message: message.to_string(),
+                id: *id,
+            });
+            true
+        })?;
+        Ok(stashes)
+    }
+
+    /// files the stash `id` changes: its tracked changes, then the untracked files it saved
+    fn get_stash_files(&self, id: Oid) -> Result<Vec<(FileStatus, String)>, GitError> {
+        let stash = self.repo.find_commit(id)?;
+        let base = stash.parent(0)?.tree()?;
+        let diff = self
+            .repo
+            .diff_tree_to_tree(Some(&base), Some(&stash.tree()?), None)?;
+        let mut files: Vec<(FileStatus, String)> = diff
+            .deltas()
+            .map(|delta| {
+                let status = match delta.status() {
+                    Delta::Added => FileStatus::Added,
+                    Delta::Deleted => FileStatus::Deleted,
+                    Delta::Renamed => FileStatus::Renamed,
+                    Delta::Typechange => FileStatus::Typechange,
+                    _ => FileStatus::Modified,
+                };
+                let path = delta.new_file().path().or(delta.old_file().path());
+                (status, path_to_string(path))
+            })
+            .collect();
+
+        // stash -u keeps the untracked files in a third parent
+        if let Ok(untracked) = stash.parent(2) {
+            let diff = self
+                .repo
+                .diff_tree_to_tree(None, Some(&untracked.tree()?), None)?;
+            files.extend(diff.deltas().map(|delta| {
+                (
+                    FileStatus::Untracked,
+                    path_to_string(delta.new_file().path()),
+                )
+            }));
+        }
+        Ok(files)
+    }
+
+    /// apply `entry` on the working tree and return the files left in conflict. A pop drops
+    /// the stash only when it applied cleanly, like `git stash pop`
+    fn apply_stash(&mut self, entry: &StashEntry, pop: bool) -> Result<Vec<String>, GitError> {
+        self.check_stash(entry)?;
+        let mut conflicts = Vec::new();
+        apply_stash(&mut self.repo, entry.index, &mut conflicts)?;
+        if pop && conflicts.is_empty() {
+            self.repo.stash_drop(entry.index)?;
+        }
+        Ok(conflicts)
+    }
+
+    fn drop_stash(&mut self, entry: &StashEntry) -> Result<(), GitError> {
+        self.check_stash(entry)?;
+        self.repo.stash_drop(entry.index)
+    }
+}
+
+impl Git {
+    /// refuse when stash@{n} is no longer the stash shown, another tool pushed or dropped one
+    fn check_stash(&mut self, entry: &StashEntry) -> Result<(), GitError> {
+        let stashes = self.get_stashes()?;
+        match stashes.get(entry.index) {
+            Some(current) if current.id == entry.id => Ok(()),
+            _ => Err(GitError::from_str(
+                "The stash list changed since it was shown, refresh it and try again",
+            )),
+        }
+    }
+}
+
+/// apply stash@{`index`} without dropping it, the working tree must not lose local changes.
+/// Conflicts go to `conflicts`: the files the apply cannot write and the untracked files it
+/// merges with conflict markers into tracked ones, then, once applied, the conflicts of the
+/// index
+pub fn apply_stash(
+    repo: &mut Repository,
+    index: usize,
+    conflicts: &mut Vec<String>,
+) -> Result<(), GitError> {
+    let mut notified = Vec::new();
+    let applied = {
+        let mut checkout_builder = CheckoutBuilder::new();
+        checkout_builder
+            .notify_on(CheckoutNotificationType::CONFLICT | CheckoutNotificationType::DIRTY)
+            .notify(|_, path, _, _, _| {
+                if let Some(path) = path {
+                    notified.push(path.display().to_string());
+                }
+                true
+            });
+        let mut options = StashApplyOptions::new();
+        options.checkout_options(checkout_builder);
+        repo.stash_apply(index, Some(&mut options))
+    };
+    conflicts.extend(notified);
+    applied?;
+    for path in get_conflicted_paths(repo)? {
+        if !conflicts.contains(&path) {
+            conflicts.push(path);
+        }
+    }
+    Ok(())
+}
+
+fn path_to_string(path: Option<&std::path::Path>) -> String {
+    path.map(|path| path.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use git2::{Signature, StashFlags};
+    use std::{
+        env, fs,
+        path::{Path, PathBuf},
+    };
+
+    /// repository with `a` committed as "1", then changed to "2" and a new `b`, both stashed
+    fn stashed_repo(name: &str) -> (PathBuf, Git) {
+        let workdir = env::temp_dir().join(format!("git-ui-stash-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&workdir);
+        let mut repo = Repository::init(&workdir).unwrap();
+        let signature = Signature::now("test", "test@example.com").unwrap();
+        fs::write(workdir.join("a"), "1\n").unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new("a")).unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        repo.commit(Some("HEAD"), &signature, &signature, "a", &tree, &[])
+            .unwrap();
+        drop(tree);
+
+        fs::write(workdir.join("a"), "2\n").unwrap();
+        fs::write(workdir.join("b"), "new\n").unwrap();
+        repo.stash_save(&signature, "work", Some(StashFlags::INCLUDE_UNTRACKED))
+            .unwrap();
+        (workdir, Git::new(repo))
+    }
+
+    #[test]
+    fn stash_files_list_tracked_then_untracked() {
+        let (workdir, mut git) = stashed_repo("files");
+        let stashes = git.get_stashes().unwrap();
+        assert_eq!(stashes.len(), 1);
+        assert_eq!(stashes[0].index, 0);
+        assert!(stashes[0].message.ends_with("work"));
+
+        let files = git.get_stash_files(stashes[0].id).unwrap();
+        assert_eq!(
+            files,
+            vec![
+                (FileStatus::Modified, "a".to_string()),
+                (FileStatus::Untracked, "b".to_string()),
+            ]
+        );
+        fs::remove_dir_all(&workdir).unwrap();
+    }
+
+    #[test]
+    fn pop_drops_a_clean_stash_and_apply_keeps_it() {
+        let (workdir, mut git) = stashed_repo("pop");
+        let stash = git.get_stashes().unwrap().remove(0);
+        assert!(git.apply_stash(&stash, false).unwrap().is_empty());
+        assert_eq!(fs::read_to_string(workdir.join("a")).unwrap(), "2\n");
+        assert_eq!(git.get_stashes().unwrap().len(), 1);
+
+        // the same changes again: the untracked file is already there, nothing is overwritten
+        assert!(git.apply_stash(&stash, true).is_err());
+        assert_eq!(git.get_stashes().unwrap().len(), 1);
+
+        fs::write(workdir.join("a"), "1\n").unwrap();
+        fs::remove_file(workdir.join("b")).unwrap();
+        assert!(git.apply_stash(&stash, true).unwrap().is_empty());
+        assert_eq!(fs::read_to_string(workdir.join("b")).unwrap(), "new\n");
+        assert!(git.get_stashes().unwrap().is_empty());
+        fs::remove_dir_all(&workdir).unwrap();
+    }
+
+    #[test]
+    fn pop_keeps_a_conflicting_stash() {
+        let (workdir, mut git) = stashed_repo("conflict");
+        let stash = git.get_stashes().unwrap().remove(0);
+        // a tracked `b` on HEAD collides with the untracked `b` of the stash
+        fs::write(workdir.join("b"), "committed\n").unwrap();
+        let signature = Signature::now("test", "test@example.com").unwrap();
+        let mut index = git.repo.index().unwrap();
+        index.add_path(Path::new("b")).unwrap();
+        index.write().unwrap();
+        let tree = git.repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let head = git.repo.head().unwrap().peel_to_commit().unwrap();
+        git.repo
+            .commit(Some("HEAD"), &signature, &signature, "b", &tree, &[&head])
+            .unwrap();
+        drop((tree, head));
+
+        let conflicts = git.apply_stash(&stash, true).unwrap();
+        assert_eq!(conflicts, vec!["b".to_string()]);
+        assert_eq!(git.get_stashes().unwrap().len(), 1);
+        fs::remove_dir_all(&workdir).unwrap();
+    }
+
+    #[test]
+    fn drop_refuses_a_stash_that_moved() {
+        let (workdir, mut git) = stashed_repo("drop");
+        let stash = git.get_stashes().unwrap().remove(0);
+        let moved = StashEntry {
+            index: 0,
+            message: stash.message.clone(),
+            id: Oid::zero(),
+        };
+        assert!(git.drop_stash(&moved).is_err());
+        assert_eq!(git.get_stashes().unwrap().len(), 1);
+
+        git.drop_stash(&stash).unwrap();
+        assert!(git.get_stashes().unwrap().is_empty());
+        fs::remove_dir_all(&workdir).unwrap();
+    }
+}
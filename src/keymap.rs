@@ -0,0 +1,374 @@
+use crossterm::event::KeyCode;
+
+use crate::pages::Pages;
+
+/// an action of a page, bound to `key` unless the configuration moves it
+pub struct Action {
+    pub name: &'static str,
+    pub key: KeyCode,
+    /// blocks of the page where the action works, every block when empty
+    pub blocks: &'static [&'static str],
+    /// changes the repository, refused in --readonly mode
+    pub mutating: bool,
+    pub description: &'static str,
+}
+
+impl Action {
+    pub fn is_available(&self, block: Option<&str>) -> bool {
+        self.blocks.is_empty() || block.is_some_and(|block| self.blocks.contains(&block))
+    }
+}
+
+const fn action(
+    name: &'static str,
+    key: KeyCode,
+    blocks: &'static [&'static str],
+    mutating: bool,
+    description: &'static str,
+) -> Action {
+    Action {
+        name,
+        key,
+        blocks,
+        mutating,
+        description,
+    }
+}
+
+const FILES: &[&str] = &["unstaged", "staged"];
+
+const STATUS_ACTIONS: [Action; 18] = [
+    action(
+        "commit",
+        KeyCode::Char('c'),
+        &[],
+        true,
+        "commit the staged files",
+    ),
+    action(
+        "push",
+        KeyCode::Char('p'),
+        &[],
+        true,
+        "push the current branch",
+    ),
+    action(
+        "add",
+        KeyCode::Char('a'),
+        &["unstaged"],
+        true,
+        "stage the selected or marked files",
+    ),
+    action(
+        "add_all",
+        KeyCode::Char('A'),
+        &[],
+        true,
+        "stage every file (the filtered ones when filtering)",
+    ),
+    action(
+        "restore",
+        KeyCode::Char('r'),
+        &["staged"],
+        true,
+        "unstage the selected or marked files",
+    ),
+    action(
+        "restore_all",
+        KeyCode::Char('U'),
+        &[],
+        true,
+        "unstage every file (the filtered ones when filtering)",
+    ),
+    action(
+        "discard",
+        KeyCode::Char('d'),
+        &["unstaged"],
+        true,
+        "discard the changes of the selected or marked files",
+    ),
+    action(
+        "discard_all",
+        KeyCode::Char('D'),
+        &["unstaged"],
+        true,
+        "discard every change",
+    ),
+    action(
+        "mark",
+        KeyCode::Char(' '),
+        FILES,
+        false,
+        "mark / unmark the selected file (Shift+Up/Down to extend)",
+    ),
+    action(
+        "filter",
+        KeyCode::Char('/'),
+        &[],
+        false,
+        "filter the files by path",
+    ),
+    action(
+        "ignore",
+        KeyCode::Char('i'),
+        &["unstaged"],
+        true,
+        "add the selected file to an ignore file",
+    ),
+    action(
+        "show_ignored",
+        KeyCode::Char('I'),
+        &[],
+        false,
+        "show / hide the ignored files",
+    ),
+    action(
+        "expand_untracked",
+        KeyCode::Char('u'),
+        &[],
+        false,
+        "list the files of untracked directories",
+    ),
+    action(
+        "tree",
+        KeyCode::Char('t'),
+        &[],
+        false,
+        "switch between the tree and the flat list",
+    ),
+    action(
+        "collapse",
+        KeyCode::Enter,
+        FILES,
+        false,
+        "fold / unfold the selected directory",
+    ),
+    action(
+        "previous_hunk",
+        KeyCode::Char('['),
+        &["diff"],
+        false,
+        "select the previous hunk",
+    ),
+    action(
+        "next_hunk",
+        KeyCode::Char(']'),
+        &["diff"],
+        false,
+        "select the next hunk",
+    ),
+    action(
+        "discard_hunk",
+        KeyCode::Char('d'),
+        &["diff"],
+        true,
+        "discard the selected hunk",
+    ),
+];
+
+const BRANCH_ACTIONS: [Action; 7] = [
+    action(
+        "checkout",
+        KeyCode::Char('c'),
+        &[],
+        true,
+        "check out the selected branch",
+    ),
+    action(
+        "new",
+        KeyCode::Char('n'),
+        &[],
+        true,
+        "create a branch on HEAD",
+    ),
+    action(
+        "delete",
+        KeyCode::Char('d'),
+        &[],
+        true,
+        "delete the selected branch (on its remote in the Remote block)",
+    ),
+    action(
+        "log",
+        KeyCode::Char('l'),
+        &[],
+        false,
+        "show the history of the selected branch",
+    ),
+    action(
+        "rename",
+        KeyCode::Char('r'),
+        &["local"],
+        true,
+        "rename the selected branch",
+    ),
+    action(
+        "set_upstream",
+        KeyCode::Char('u'),
+        &["remote"],
+        true,
+        "make the selected local branch track the selected remote branch",
+    ),
+    action(
+        "unset_upstream",
+        KeyCode::Char('U'),
+        &["local"],
+        true,
+        "stop tracking the upstream of the selected branch",
+    ),
+];
+
+const LOG_ACTIONS: [Action; 7] = [
+    action(
+        "range",
+        KeyCode::Char('v'),
+        &[],
+        false,
+        "start / stop a range selection",
+    ),
+    action(
+        "trailer",
+        KeyCode::Char('x'),
+        &[],
+        false,
+        "toggle the (cherry picked from commit ...) trailer",
+    ),
+    action(
+        "cherry_pick",
+        KeyCode::Char('c'),
+        &[],
+        true,
+        "cherry-pick the selected commit or range",
+    ),
+    action(
+        "revert",
+        KeyCode::Char('r'),
+        &[],
+        true,
+        "revert the selected commit",
+    ),
+    action(
+        "reset_soft",
+        KeyCode::Char('s'),
+        &[],
+        true,
+        "soft reset of HEAD to the selected commit",
+    ),
+    action(
+        "reset_mixed",
+        KeyCode::Char('m'),
+        &[],
+        true,
+        "mixed reset of HEAD to the selected commit",
+    ),
+    action(
+        "reset_hard",
+        KeyCode::Char('h'),
+        &[],
+        true,
+        "hard reset of HEAD to the selected commit",
+    ),
+];
+
+const REFLOG_ACTIONS: [Action; 1] = [action(
+    "restore",
+    KeyCode::Char('r'),
+    &["entries"],
+    true,
+    "move the ref back to the selected entry",
+)];
+
+const CONFIG_ACTIONS: [Action; 3] = [
+    action(
+        "edit",
+        KeyCode::Char('e'),
+        &[],
+        true,
+        "edit the selected value",
+    ),
+    action(
+        "add",
+        KeyCode::Char('a'),
+        &[],
+        true,
+        "add an entry to the local or global configuration",
+    ),
+    action(
+        "delete",
+        KeyCode::Char('d'),
+        &[],
+        true,
+        "remove the selected entry",
+    ),
+];
+
+const STASH_ACTIONS: [Action; 3] = [
+    action(
+        "apply",
+        KeyCode::Char('a'),
+        &[],
+        true,
+        "apply the selected stash and keep it",
+    ),
+    action(
+        "pop",
+        KeyCode::Char('p'),
+        &[],
+        true,
+        "apply the selected stash and drop it unless it conflicts",
+    ),
+    action(
+        "drop",
+        KeyCode::Char('d'),
+        &[],
+        true,
+        "drop the selected stash",
+    ),
+];
+
+/// keys working on every page, they cannot be moved
+pub const GLOBAL_KEYS: [(&str, &str); 8] = [
+    ("Up / Down", "move in the focused block"),
+    (
+        "PgUp / PgDn / Home / End",
+        "move by a page, to the top or to the bottom",
+    ),
+    ("Ctrl+direction", "focus another block"),
+    ("1-6", "switch page"),
+    ("Ctrl+z", "undo the last operation"),
+    ("?", "show this help"),
+    (": / Ctrl+p", "search and run an action"),
+    ("q / Esc", "quit"),
+];
+
+/// moves of `page` that are not actions, they cannot be moved either
+pub fn get_page_keys(page: &Pages) -> &'static [(&'static str, &'static str)] {
+    match page {
+        Pages::StatusPAGE => &[(
+            "Alt+letter",
+            "select the next file starting with the letter",
+        )],
+        Pages::BranchPAGE => &[(
+            "Alt+letter",
+            "select the next branch starting with the letter",
+        )],
+        _ => &[],
+    }
+}
+
+/// the action of `page` called `name`
+pub fn find_action(page: &Pages, name: &str) -> Option<&'static Action> {
+    get_actions(page).iter().find(|action| action.name == name)
+}
+
+/// every action of `page`, with its built-in key
+pub fn get_actions(page: &Pages) -> &'static [Action] {
+    match page {
+        Pages::StatusPAGE => &STATUS_ACTIONS,
+        Pages::BranchPAGE => &BRANCH_ACTIONS,
+        Pages::LogPAGE => &LOG_ACTIONS,
+        Pages::ReflogPAGE => &REFLOG_ACTIONS,
+        Pages::ConfigPage => &CONFIG_ACTIONS,
+        Pages::StashPAGE => &STASH_ACTIONS,
+    }
+}
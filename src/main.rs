@@ -1,10 +1,10 @@
-use std::io;
+use std::{env, io};
 
 mod app;
 use app::App;
 
 mod tabs;
-use tabs::StatusTab;
+use tabs::{ConfigTab, LogTab, ReflogTab, StashTab, StatusTab};
 
 mod git;
 use git::{get_repository, Git};
@@ -12,29 +12,100 @@ use git::{get_repository, Git};
 mod pages;
 use pages::Pages;
 
+mod cli;
+mod config;
+use config::Config;
+use cli::{parse_args, Command, USAGE};
+
+mod fuzzy;
+mod keymap;
+mod palette;
+use palette::Palette;
 mod popup;
+use popup::Help;
 
 use crate::tabs::BranchTab;
 
 fn main() -> io::Result<()> {
+    let cli = match parse_args(env::args().skip(1)) {
+        Ok(Command::Run(cli)) => cli,
+        Ok(Command::Help) => {
+            println!("{}", USAGE);
+            return Ok(());
+        }
+        Ok(Command::Version) => {
+            println!("git-ui {}", env!("CARGO_PKG_VERSION"));
+            return Ok(());
+        }
+        Err(message) => {
+            eprintln!("❌ {}", message);
+            eprintln!("{}", USAGE);
+            std::process::exit(2);
+        }
+    };
+    if let Some(config) = &cli.config
+        && !config.is_file()
+    {
+        eprintln!("❌ Config file {} not found", config.display());
+        std::process::exit(1);
+    }
+    // like `git -C`: everything, git subprocesses included, runs from that directory
+    if let Some(dir) = &cli.start_dir
+        && let Err(e) = env::set_current_dir(dir)
+    {
+        eprintln!("❌ Cannot open {}: {}", dir.display(), e);
+        std::process::exit(1);
+    }
+
     let repository = match get_repository() {
         Ok(repo) => repo,
-        Err(_e) => {
+        Err(e) => {
             eprintln!("❌ Failed to find repository");
-            eprintln!("Ether to deep or missing git folder");
+            eprintln!("{}", e.message());
             std::process::exit(1);
         }
     };
 
+    // a bare repository has no working tree: only its history and branches can be browsed
+    let bare = repository.is_bare();
+    let mut config = match Config::load(cli.config.as_deref(), repository.path()) {
+        Ok(config) => config,
+        Err(message) => {
+            eprintln!("❌ {}", message);
+            std::process::exit(1);
+        }
+    };
+    if let Some(theme) = &cli.theme {
+        config.set_syntax_theme(theme);
+    }
+
+    let mut git = Git::new(repository);
+    git.readonly = cli.readonly || bare;
+    git.config = config;
+
     let mut terminal = ratatui::init();
 
     let mut program = App {
         exit: false,
         page: Pages::StatusPAGE,
+        mouse: cli.mouse && git.config.mouse,
         status_page: StatusTab::new().into(),
         branch_page: BranchTab::new(),
-        git: Git::new(repository),
+        log_page: LogTab::new(),
+        reflog_page: ReflogTab::new(),
+        config_page: ConfigTab::new(),
+        stash_page: StashTab::new(),
+        help: Help::new(),
+        palette: Palette::new(),
+        last_click: None,
+        status_outdated: false,
+        git,
     };
+    if bare && cli.page == Pages::StatusPAGE {
+        program.open_page(Pages::LogPAGE);
+    } else {
+        program.open_page(cli.page);
+    }
 
     program.run(&mut terminal)?;
     ratatui::restore();
@@ -1,17 +1,34 @@
-#[derive(PartialEq, Eq)]
+#[derive(PartialEq, Eq, Clone, Copy)]
 #[repr(usize)]
 pub enum Pages {
     StatusPAGE,
     BranchPAGE,
+    LogPAGE,
+    ReflogPAGE,
     ConfigPage,
+    StashPAGE,
 }
 
 impl Pages {
-    pub fn to_index(&self) -> usize {
+    pub fn to_index(self) -> usize {
         match self {
             Pages::StatusPAGE => 0,
             Pages::BranchPAGE => 1,
-            Pages::ConfigPage => 2,
+            Pages::LogPAGE => 2,
+            Pages::ReflogPAGE => 3,
+            Pages::ConfigPage => 4,
+            Pages::StashPAGE => 5,
+        }
+    }
+
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            Pages::StatusPAGE => "Status",
+            Pages::BranchPAGE => "Branch",
+            Pages::LogPAGE => "Log",
+            Pages::ReflogPAGE => "Reflog",
+            Pages::ConfigPage => "Config",
+            Pages::StashPAGE => "Stash",
         }
     }
 
@@ -19,7 +36,10 @@ impl Pages {
         match value {
             0 => Pages::StatusPAGE,
             1 => Pages::BranchPAGE,
-            2 => Pages::ConfigPage,
+            2 => Pages::LogPAGE,
+            3 => Pages::ReflogPAGE,
+            4 => Pages::ConfigPage,
+            5 => Pages::StashPAGE,
             _ => Pages::StatusPAGE,
         }
     }
@@ -0,0 +1,178 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    layout::{Constraint, Flex, Layout, Position, Rect},
+    style::{Color, Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, Clear, List, ListItem, ListState, Paragraph},
+    Frame,
+};
+
+use crate::{
+    config::{get_key_name, Config},
+    fuzzy::tight_match,
+    keymap::{get_actions, Action},
+    pages::Pages,
+    popup::Popup,
+};
+
+const PAGES: [Pages; 6] = [
+    Pages::StatusPAGE,
+    Pages::BranchPAGE,
+    Pages::LogPAGE,
+    Pages::ReflogPAGE,
+    Pages::ConfigPage,
+    Pages::StashPAGE,
+];
+
+/// height of the list of actions, in lines
+const NB_SHOWN: u16 = 12;
+
+/// every action of every page, searched by name and description
+pub struct Palette {
+    pub input: Popup,
+    pub pos: usize,
+    pub activated: bool,
+    /// page the palette was opened from, its actions come first
+    current: Pages,
+}
+
+struct PaletteMatch {
+    page: Pages,
+    action: &'static Action,
+    text: String,
+    indices: Vec<usize>,
+}
+
+impl Palette {
+    pub fn new() -> Self {
+        Palette {
+            input: Popup::new(),
+            pos: 0,
+            activated: false,
+            current: Pages::StatusPAGE,
+        }
+    }
+
+    pub fn open(&mut self, current: Pages) {
+        self.input.set_input("");
+        self.pos = 0;
+        self.current = current;
+        self.activated = true;
+    }
+
+    /// the page and action picked with Enter
+    pub fn handle_key_event(&mut self, key_event: KeyEvent) -> Option<(Pages, &'static Action)> {
+        match key_event.code {
+            KeyCode::Esc => self.activated = false,
+            KeyCode::Enter => {
+                self.activated = false;
+                return self
+                    .get_matches()
+                    .into_iter()
+                    .nth(self.pos)
+                    .map(|found| (found.page, found.action));
+            }
+            KeyCode::Up => self.pos = self.pos.saturating_sub(1),
+            KeyCode::Down if self.pos + 1 < self.get_matches().len() => self.pos += 1,
+            KeyCode::Char(to_insert) => {
+                self.input.enter_char(to_insert);
+                self.pos = 0;
+            }
+            KeyCode::Backspace => {
+                self.input.delete_char();
+                self.pos = 0;
+            }
+            KeyCode::Left => self.input.move_cursor_left(),
+            KeyCode::Right => self.input.move_cursor_right(),
+            _ => {}
+        }
+        None
+    }
+
+    /// actions matching the input, tightest matches first, then those of the current page
+    fn get_matches(&self) -> Vec<PaletteMatch> {
+        let mut matches: Vec<PaletteMatch> = PAGES
+            .iter()
+            .flat_map(|page| get_actions(page).iter().map(move |action| (*page, action)))
+            .filter_map(|(page, action)| {
+                let text = format!(
+                    "{} {}: {}",
+                    page.get_name(),
+                    action.name,
+                    action.description
+                );
+                let indices = tight_match(&self.input.input, &text)?;
+                Some(PaletteMatch {
+                    page,
+                    action,
+                    text,
+                    indices,
+                })
+            })
+            .collect();
+        matches.sort_by_key(|found| {
+            let spread = match (found.indices.first(), found.indices.last()) {
+                (Some(first), Some(last)) => last - first,
+                _ => 0,
+            };
+            (spread, found.page != self.current)
+        });
+        matches
+    }
+
+    pub fn draw_popup(&self, frame: &mut Frame, content: Rect, config: &Config) {
+        let matches = self.get_matches();
+        let items: Vec<ListItem> = matches
+            .iter()
+            .map(|found| {
+                let key = get_key_name(config.get_key(&found.page, found.action));
+                let mut spans = vec![Span::from(format!("{:>7}  ", key)).cyan()];
+                spans.extend(highlight(&found.text, &found.indices));
+                ListItem::new(Line::from(spans))
+            })
+            .collect();
+        let list = List::new(items)
+            .block(Block::bordered().title(format!("Actions ({})", matches.len())))
+            .highlight_style(Style::default().bg(Color::DarkGray));
+        let input = Paragraph::new(self.input.input.clone())
+            .block(Block::bordered().title("Run an action (Enter: run / Esc: cancel)"));
+
+        let vertical = Layout::vertical([Constraint::Length(NB_SHOWN + 5)]).flex(Flex::Center);
+        let horizontal = Layout::horizontal([Constraint::Percentage(70)]).flex(Flex::Center);
+        let [content] = vertical.areas(content);
+        let [content] = horizontal.areas(content);
+        let [input_area, list_area] =
+            Layout::vertical([Constraint::Length(3), Constraint::Fill(1)]).areas(content);
+
+        let before_cursor = self.input.input.chars().take(self.input.character_index);
+        frame.set_cursor_position(Position::new(
+            input_area.x + 1 + before_cursor.count() as u16,
+            input_area.y + 1,
+        ));
+        let mut state = ListState::default().with_selected(Some(self.pos));
+        frame.render_widget(Clear, content);
+        frame.render_widget(input, input_area);
+        frame.render_stateful_widget(list, list_area, &mut state);
+    }
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Palette::new()
+    }
+}
+
+/// `text` with the chars at `indices` underlined
+fn highlight(text: &str, indices: &[usize]) -> Vec<Span<'static>> {
+    let match_style = Style::default().underlined().bold();
+    text.chars()
+        .enumerate()
+        .map(|(i, c)| {
+            if indices.contains(&i) {
+                Span::styled(c.to_string(), match_style)
+            } else {
+                Span::raw(c.to_string())
+            }
+        })
+        .collect()
+}
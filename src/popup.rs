@@ -1,5 +1,8 @@
+use crossterm::event::{KeyCode, KeyEvent};
 use ratatui::{
     layout::{Constraint, Flex, Layout, Position, Rect},
+    style::Stylize,
+    text::{Line, Span},
     widgets::{Block, Clear, Paragraph},
     Frame,
 };
@@ -21,6 +24,11 @@ impl Popup {
         }
     }
 
+    pub fn set_input(&mut self, text: &str) {
+        self.input = text.to_string();
+        self.character_index = self.input.chars().count();
+    }
+
     pub fn enter_char(&mut self, new_char: char) {
         let index = self.byte_index();
         self.input.insert(index, new_char);
@@ -75,14 +83,25 @@ impl Popup {
         let block = Block::bordered().title(name_block);
         let text = Paragraph::new(self.input.clone()).block(block);
 
-        let vertical = Layout::vertical([Constraint::Max(4)]).flex(Flex::Center);
+        // generated messages (cherry-pick, revert) can span several lines
+        let nb_lines = self.input.lines().count().max(1) as u16;
+        let before_cursor: String = self.input.chars().take(self.character_index).collect();
+        let cursor_line = before_cursor.matches('\n').count() as u16;
+        let cursor_column = before_cursor
+            .rsplit('\n')
+            .next()
+            .unwrap_or("")
+            .chars()
+            .count() as u16;
+
+        let vertical = Layout::vertical([Constraint::Max(nb_lines + 3)]).flex(Flex::Center);
         let horizontal = Layout::horizontal([Constraint::Percentage(60)]).flex(Flex::Center);
         let [content] = vertical.areas(content);
         let [content] = horizontal.areas(content);
 
         frame.set_cursor_position(Position::new(
-            content.x + 1 + self.character_index as u16,
-            content.y + 1,
+            content.x + 1 + cursor_column,
+            content.y + 1 + cursor_line,
         ));
         frame.render_widget(Clear, content);
         frame.render_widget(text, content);
@@ -94,3 +113,309 @@ impl Default for Popup {
         Popup::new()
     }
 }
+
+pub struct Notification {
+    pub title: String,
+    pub lines: Vec<String>,
+    pub activated: bool,
+}
+
+impl Notification {
+    pub fn new() -> Self {
+        Notification {
+            title: String::new(),
+            lines: Vec::new(),
+            activated: false,
+        }
+    }
+
+    pub fn show(&mut self, title: &str, lines: Vec<String>) {
+        self.title = title.to_string();
+        self.lines = lines;
+        self.activated = true;
+    }
+
+    pub fn draw_popup(&self, frame: &mut Frame, content: Rect) {
+        let block = Block::bordered().title(self.title.clone());
+        let mut text = self.lines.join("\n");
+        text.push_str("\n\npress any key to close");
+        let height = self.lines.len() as u16 + 4;
+        let text = Paragraph::new(text).centered().block(block);
+
+        let vertical = Layout::vertical([Constraint::Length(height)]).flex(Flex::Center);
+        let horizontal = Layout::horizontal([Constraint::Percentage(60)]).flex(Flex::Center);
+        let [content] = vertical.areas(content);
+        let [content] = horizontal.areas(content);
+
+        frame.render_widget(Clear, content);
+        frame.render_widget(text, content);
+    }
+}
+
+impl Default for Notification {
+    fn default() -> Self {
+        Notification::new()
+    }
+}
+
+pub struct Confirm<A> {
+    pub title: String,
+    pub lines: Vec<String>,
+    pub action: Option<A>,
+    pub expected: Option<String>,
+    pub input: Popup,
+}
+
+impl<A> Confirm<A> {
+    pub fn new() -> Self {
+        Confirm {
+            title: String::new(),
+            lines: Vec::new(),
+            action: None,
+            expected: None,
+            input: Popup::new(),
+        }
+    }
+
+    /// ask the user to confirm `action` before running it
+    pub fn ask(&mut self, title: &str, lines: Vec<String>, action: A) {
+        self.title = title.to_string();
+        self.lines = lines;
+        self.action = Some(action);
+        self.expected = None;
+    }
+
+    /// stronger confirmation: `expected` has to be typed before Enter runs `action`
+    pub fn ask_typed(&mut self, title: &str, lines: Vec<String>, expected: &str, action: A) {
+        self.ask(title, lines, action);
+        self.expected = Some(expected.to_string());
+        self.input.set_input("");
+    }
+
+    pub fn is_activated(&self) -> bool {
+        self.action.is_some()
+    }
+
+    /// keys answering the popup, None when it is closed
+    pub fn get_help(&self) -> Option<Vec<(String, String)>> {
+        self.action.as_ref()?;
+        let confirm = match &self.expected {
+            Some(expected) => (format!("'{}' Enter", expected), "confirm".to_string()),
+            None => ("y / Enter".to_string(), "confirm".to_string()),
+        };
+        Some(vec![confirm, ("n / Esc".to_string(), "cancel".to_string())])
+    }
+
+    /// return the action once confirmed, close the popup on cancel
+    pub fn handle_key_event(&mut self, key_event: KeyEvent) -> Option<A> {
+        if let Some(expected) = &self.expected {
+            return match key_event.code {
+                KeyCode::Enter if &self.input.input == expected => self.action.take(),
+                KeyCode::Esc => {
+                    self.action = None;
+                    None
+                }
+                KeyCode::Char(to_insert) => {
+                    self.input.enter_char(to_insert);
+                    None
+                }
+                KeyCode::Backspace => {
+                    self.input.delete_char();
+                    None
+                }
+                _ => None,
+            };
+        }
+        match key_event.code {
+            KeyCode::Char('y') | KeyCode::Enter => self.action.take(),
+            KeyCode::Char('n') | KeyCode::Esc => {
+                self.action = None;
+                None
+            }
+            _ => None,
+        }
+    }
+
+    pub fn draw_popup(&self, frame: &mut Frame, content: Rect) {
+        let block = Block::bordered().title(self.title.clone());
+        let mut text = self.lines.join("\n");
+        match &self.expected {
+            Some(expected) => text.push_str(&format!(
+                "\n\ntype '{}' then Enter to confirm / Esc: cancel\n> {}",
+                expected, self.input.input
+            )),
+            None => text.push_str("\n\ny: confirm / n: cancel"),
+        }
+        let height = self.lines.len() as u16 + if self.expected.is_some() { 5 } else { 4 };
+        let text = Paragraph::new(text).centered().block(block);
+
+        let vertical = Layout::vertical([Constraint::Length(height)]).flex(Flex::Center);
+        let horizontal = Layout::horizontal([Constraint::Percentage(60)]).flex(Flex::Center);
+        let [content] = vertical.areas(content);
+        let [content] = horizontal.areas(content);
+
+        frame.render_widget(Clear, content);
+        frame.render_widget(text, content);
+    }
+}
+
+impl<A> Default for Confirm<A> {
+    fn default() -> Self {
+        Confirm::new()
+    }
+}
+
+pub struct Choice<A> {
+    pub title: String,
+    pub lines: Vec<String>,
+    pub options: Vec<(char, String, A)>,
+}
+
+impl<A> Choice<A> {
+    pub fn new() -> Self {
+        Choice {
+            title: String::new(),
+            lines: Vec::new(),
+            options: Vec::new(),
+        }
+    }
+
+    /// let the user pick one of `options` by pressing its key
+    pub fn ask(&mut self, title: &str, lines: Vec<String>, options: Vec<(char, String, A)>) {
+        self.title = title.to_string();
+        self.lines = lines;
+        self.options = options;
+    }
+
+    pub fn is_activated(&self) -> bool {
+        !self.options.is_empty()
+    }
+
+    /// keys answering the popup, None when it is closed
+    pub fn get_help(&self) -> Option<Vec<(String, String)>> {
+        if !self.is_activated() {
+            return None;
+        }
+        let mut keys: Vec<(String, String)> = self
+            .options
+            .iter()
+            .map(|(key, label, _)| (key.to_string(), label.clone()))
+            .collect();
+        keys.push(("Esc".to_string(), "cancel".to_string()));
+        Some(keys)
+    }
+
+    /// return the picked action, close the popup on Esc
+    pub fn handle_key_event(&mut self, key_event: KeyEvent) -> Option<A> {
+        match key_event.code {
+            KeyCode::Esc => {
+                self.options.clear();
+                None
+            }
+            KeyCode::Char(key) => {
+                let index = self
+                    .options
+                    .iter()
+                    .position(|(option, _, _)| *option == key)?;
+                let (_, _, action) = self.options.swap_remove(index);
+                self.options.clear();
+                Some(action)
+            }
+            _ => None,
+        }
+    }
+
+    pub fn draw_popup(&self, frame: &mut Frame, content: Rect) {
+        let block = Block::bordered().title(self.title.clone());
+        let mut lines = self.lines.clone();
+        if !lines.is_empty() {
+            lines.push(String::new());
+        }
+        lines.extend(
+            self.options
+                .iter()
+                .map(|(key, label, _)| format!("{}: {}", key, label)),
+        );
+        lines.push(String::new());
+        lines.push("Esc: cancel".to_string());
+        let height = lines.len() as u16 + 2;
+        let text = Paragraph::new(lines.join("\n")).centered().block(block);
+
+        let vertical = Layout::vertical([Constraint::Length(height)]).flex(Flex::Center);
+        let horizontal = Layout::horizontal([Constraint::Percentage(60)]).flex(Flex::Center);
+        let [content] = vertical.areas(content);
+        let [content] = horizontal.areas(content);
+
+        frame.render_widget(Clear, content);
+        frame.render_widget(text, content);
+    }
+}
+
+impl<A> Default for Choice<A> {
+    fn default() -> Self {
+        Choice::new()
+    }
+}
+
+/// keys of the focused block and popup, grouped by section
+pub struct Help {
+    pub title: String,
+    pub sections: Vec<(String, Vec<(String, String)>)>,
+    pub activated: bool,
+}
+
+impl Help {
+    pub fn new() -> Self {
+        Help {
+            title: String::new(),
+            sections: Vec::new(),
+            activated: false,
+        }
+    }
+
+    pub fn show(&mut self, title: &str, sections: Vec<(String, Vec<(String, String)>)>) {
+        self.title = title.to_string();
+        self.sections = sections;
+        self.activated = true;
+    }
+
+    pub fn draw_popup(&self, frame: &mut Frame, content: Rect) {
+        let key_width = self
+            .sections
+            .iter()
+            .flat_map(|(_, keys)| keys.iter().map(|(key, _)| key.chars().count()))
+            .max()
+            .unwrap_or(0);
+        let mut lines: Vec<Line> = Vec::new();
+        for (name, keys) in &self.sections {
+            if !lines.is_empty() {
+                lines.push(Line::from(""));
+            }
+            lines.push(Line::from(name.clone()).bold());
+            lines.extend(keys.iter().map(|(key, description)| {
+                Line::from(vec![
+                    Span::from(format!("  {:width$}  ", key, width = key_width)).cyan(),
+                    Span::from(description.clone()),
+                ])
+            }));
+        }
+        lines.push(Line::from(""));
+        lines.push(Line::from("press any key to close").centered());
+        let height = lines.len() as u16 + 2;
+        let text = Paragraph::new(lines).block(Block::bordered().title(self.title.clone()));
+
+        let vertical = Layout::vertical([Constraint::Length(height)]).flex(Flex::Center);
+        let horizontal = Layout::horizontal([Constraint::Percentage(60)]).flex(Flex::Center);
+        let [content] = vertical.areas(content);
+        let [content] = horizontal.areas(content);
+
+        frame.render_widget(Clear, content);
+        frame.render_widget(text, content);
+    }
+}
+
+impl Default for Help {
+    fn default() -> Self {
+        Help::new()
+    }
+}
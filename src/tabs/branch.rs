@@ -2,14 +2,23 @@ use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use git2::BranchType;
 use ratatui::{
     layout::{Constraint, Layout, Rect},
-    widgets::Paragraph,
+    style::{Color, Style, Stylize},
+    text::{Line, Span},
+    widgets::{List, ListItem, Paragraph},
     Frame,
 };
+use std::cell::Cell;
 
 use crate::{
-    git::{Branch, Git},
-    popup::Popup,
-    tabs::mover::{Move, DIRECTION},
+    git::{
+        get_relative_time, get_short_id, Branch, BranchInfo, CheckoutMode, CheckoutOutcome,
+        CheckoutTarget, Git, HeadState, Journal, Undo,
+    },
+    popup::{Choice, Confirm, Popup},
+    tabs::{
+        mover::{Move, DIRECTION},
+        selection::{find_by_letter, get_jump, get_short_name, render_list},
+    },
 };
 
 #[derive(PartialEq)]
@@ -18,6 +27,27 @@ pub enum BranchBlock {
     Remote,
 }
 
+/// operations waiting for a confirmation
+pub enum BranchAction {
+    /// local branch, remote branch it will track
+    SetUpstream(String, String),
+    UnsetUpstream(String),
+    /// "origin/feature", removed from the remote
+    DeleteRemote(String),
+    DeleteLocal(String),
+}
+
+/// what the name typed in `name_popup` is for
+enum NameTarget {
+    /// the local branch renamed
+    Rename(String),
+    /// remote branch checked out under another name than a local branch already has
+    CheckoutAs(String, CheckoutMode),
+}
+
+/// unmerged commits listed before a forced deletion
+const MAX_PREVIEW_LINES: usize = 10;
+
 pub struct BranchTab {
     pub pos_local_branches: u16,
     pub pos_remote_branches: u16,
@@ -25,6 +55,15 @@ pub struct BranchTab {
     pub nb_local_branch: u16,
     pub newbranch_popup: Popup,
     pub focused_block: BranchBlock,
+    pub open_log: Option<String>,
+    /// asks a branch name for `naming`
+    pub name_popup: Popup,
+    naming: Option<NameTarget>,
+    pub confirm_popup: Confirm<BranchAction>,
+    /// what to do with the local changes blocking a checkout
+    pub checkout_popup: Choice<(CheckoutTarget, CheckoutMode)>,
+    /// rows visible in the lists at the last frame, for PageUp / PageDown
+    list_height: Cell<u16>,
 }
 
 impl BranchTab {
@@ -36,10 +75,26 @@ impl BranchTab {
             nb_remote_branch: 0,
             newbranch_popup: Popup::new(),
             focused_block: BranchBlock::Local,
+            open_log: None,
+            name_popup: Popup::new(),
+            naming: None,
+            confirm_popup: Confirm::new(),
+            checkout_popup: Choice::new(),
+            list_height: Cell::new(0),
         }
     }
 
     pub fn handle_key_event(&mut self, key_event: KeyEvent, git: &mut Git) {
+        if key_event.modifiers == KeyModifiers::ALT
+            && let KeyCode::Char(letter) = key_event.code
+        {
+            let branches = self.get_focused_branches(git);
+            let names: Vec<&str> = branches.iter().map(|name| get_short_name(name)).collect();
+            if let Some(pos) = find_by_letter(&names, self.get_focused_pos() as usize, letter) {
+                self.set_focused_pos(pos as u16);
+            }
+            return;
+        }
         if key_event.modifiers == KeyModifiers::CONTROL {
             self.change_block(key_event.code);
             return;
@@ -47,24 +102,94 @@ impl BranchTab {
         match key_event.code {
             KeyCode::Up => self.scroll_up(),
             KeyCode::Down => self.scroll_down(),
-            KeyCode::Char('c') => {
-                let (branchtype, pos): (BranchType, usize) = match self.focused_block {
-                    BranchBlock::Local => (BranchType::Local, self.pos_local_branches as usize),
-                    BranchBlock::Remote => (BranchType::Remote, self.pos_remote_branches as usize),
+            KeyCode::PageUp | KeyCode::PageDown | KeyCode::Home | KeyCode::End => {
+                let pos = self.get_focused_pos() as usize;
+                let len = self.get_focused_branches(git).len();
+                let height = self.list_height.get() as usize;
+                if let Some(pos) = get_jump(key_event.code, pos, len, height) {
+                    self.set_focused_pos(pos as u16);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// run the action called `name` (see `keymap::get_actions`) on the selected branch
+    pub fn run_action(&mut self, name: &str, git: &mut Git) {
+        match name {
+            "checkout" => {
+                let pos = self.get_focused_pos() as usize;
+                if let Some(name) = self.get_focused_branches(git).get(pos).cloned() {
+                    let target = match self.focused_block {
+                        BranchBlock::Local => CheckoutTarget::Local(name),
+                        BranchBlock::Remote => CheckoutTarget::Remote(name, None),
+                    };
+                    self.checkout(target, CheckoutMode::Safe, git);
+                }
+            }
+            "delete" if self.focused_block == BranchBlock::Remote => {
+                let Some(remote) = git
+                    .branch
+                    .remote_branches
+                    .get(self.pos_remote_branches as usize)
+                    .cloned()
+                else {
+                    return;
                 };
-                let _ = git.branch.checkout(branchtype, pos, &git.repo);
-                self.reset_branch(git);
+                self.confirm_popup.ask_typed(
+                    "Delete remote branch",
+                    vec![
+                        format!("{} will be deleted on the remote for everyone", remote),
+                        "it cannot be undone from here".to_string(),
+                    ],
+                    &remote,
+                    BranchAction::DeleteRemote(remote.clone()),
+                );
+            }
+            "delete" => {
+                if let Some(branch_name) = self.get_selected_local(git) {
+                    self.ask_delete_local(branch_name, git);
+                }
+            }
+            "new" => self.newbranch_popup.activated = true,
+            "rename" => {
+                if let Some(name) = self.get_selected_local(git) {
+                    self.name_popup.set_input(&name);
+                    self.naming = Some(NameTarget::Rename(name));
+                    self.name_popup.activated = true;
+                }
             }
-            KeyCode::Char('d') => {
-                if self.focused_block == BranchBlock::Remote {
+            "set_upstream" => {
+                let Some(local) = self.get_selected_local(git) else {
+                    return;
+                };
+                let Some(remote) = git
+                    .branch
+                    .remote_branches
+                    .get(self.pos_remote_branches as usize)
+                    .cloned()
+                else {
                     return;
+                };
+                self.confirm_popup.ask(
+                    "Set upstream",
+                    vec![format!("{} will track {}", local, remote)],
+                    BranchAction::SetUpstream(local, remote),
+                );
+            }
+            "unset_upstream" => {
+                if let Some(local) = self.get_selected_local(git) {
+                    self.confirm_popup.ask(
+                        "Unset upstream",
+                        vec![format!("{} will track no branch", local)],
+                        BranchAction::UnsetUpstream(local),
+                    );
                 }
-                let branch_name: String =
-                    git.branch.local_branches[self.pos_local_branches as usize].clone();
-                let _ = git.branch.delete_branch(&branch_name, &git.repo);
-                self.reset_branch(git);
             }
-            KeyCode::Char('n') => self.newbranch_popup.activated = true,
+            "log" => {
+                let pos = self.get_focused_pos();
+                self.open_log = self.get_focused_branches(git).get(pos as usize).cloned();
+            }
             _ => {}
         }
     }
@@ -77,23 +202,320 @@ impl BranchTab {
             KeyCode::Right => self.newbranch_popup.move_cursor_right(),
             KeyCode::Backspace => self.newbranch_popup.delete_char(),
             KeyCode::Enter => {
-                let _ = git.branch.create_branch("test", &git.repo);
+                let branch_name = self.newbranch_popup.input.clone();
+                match git.branch.create_branch(&branch_name, &git.repo) {
+                    // on a detached HEAD the new branch is where the commits go next
+                    Ok(()) if git.branch.head == HeadState::Detached => {
+                        if let Err(e) = git.repo.set_head(&format!("refs/heads/{}", branch_name)) {
+                            git.notification
+                                .show("Switch failed", vec![e.message().to_string()]);
+                        }
+                    }
+                    Ok(()) => git.record(
+                        format!("create branch {}", branch_name),
+                        Undo::Delete(branch_name),
+                    ),
+                    Err(e) => git
+                        .notification
+                        .show("New branch failed", vec![e.message().to_string()]),
+                }
                 self.reset_branch(git);
-                self.newbranch_popup.input = String::new();
+                self.newbranch_popup.set_input("");
                 self.newbranch_popup.activated = false
             }
             _ => {}
         }
     }
 
-    fn reset_branch(&mut self, git: &mut Git) {
-        self.pos_local_branches = 0;
-        self.pos_remote_branches = 0;
+    pub fn name_key_event(&mut self, key_event: KeyEvent, git: &mut Git) {
+        match key_event.code {
+            KeyCode::Esc => {
+                self.name_popup.activated = false;
+                self.naming = None;
+            }
+            KeyCode::Char(to_insert) => self.name_popup.enter_char(to_insert),
+            KeyCode::Left => self.name_popup.move_cursor_left(),
+            KeyCode::Right => self.name_popup.move_cursor_right(),
+            KeyCode::Backspace => self.name_popup.delete_char(),
+            KeyCode::Enter => {
+                let new_name = self.name_popup.input.trim().to_string();
+                self.name_popup.activated = false;
+                match self.naming.take() {
+                    Some(NameTarget::Rename(old_name)) if new_name != old_name => {
+                        self.rename(old_name, new_name, git)
+                    }
+                    Some(NameTarget::CheckoutAs(remote_branch, mode)) => self.checkout(
+                        CheckoutTarget::Remote(remote_branch, Some(new_name)),
+                        mode,
+                        git,
+                    ),
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn rename(&mut self, old_name: String, new_name: String, git: &mut Git) {
+        match git.branch.rename_branch(&old_name, &new_name, &git.repo) {
+            Ok(()) => {
+                git.record(
+                    format!("rename branch {} to {}", old_name, new_name),
+                    Undo::Rename(new_name.clone(), old_name),
+                );
+                self.reset_branch(git);
+                self.pos_local_branches = find_position(&git.branch.local_branches, Some(new_name));
+            }
+            Err(e) => git
+                .notification
+                .show("Rename failed", vec![e.message().to_string()]),
+        }
+    }
+
+    pub fn confirm_key_event(&mut self, key_event: KeyEvent, git: &mut Git) {
+        let result = match self.confirm_popup.handle_key_event(key_event) {
+            Some(BranchAction::SetUpstream(local, remote)) => {
+                git.branch.set_upstream(&local, &remote, &git.repo)
+            }
+            Some(BranchAction::UnsetUpstream(local)) => {
+                git.branch.unset_upstream(&local, &git.repo)
+            }
+            Some(BranchAction::DeleteLocal(branch_name)) => {
+                self.delete_local(branch_name, git);
+                return;
+            }
+            Some(BranchAction::DeleteRemote(remote)) => {
+                // the branch list is reloaded once the push ends
+                if let Err(e) = git.delete_remote_branch(&remote) {
+                    git.notification
+                        .show("Delete failed", vec![e.message().to_string()]);
+                }
+                return;
+            }
+            None => return,
+        };
+        if let Err(e) = result {
+            git.notification
+                .show("Upstream", vec![e.message().to_string()]);
+        }
+        self.reset_branch(git);
+    }
+
+    fn checkout(&mut self, target: CheckoutTarget, mode: CheckoutMode, git: &mut Git) {
+        let name = match &target {
+            CheckoutTarget::Local(name) | CheckoutTarget::Remote(name, _) => name.clone(),
+        };
+        match git.branch.checkout(&target, mode, &mut git.repo) {
+            Ok(CheckoutOutcome::NameTaken(short_name)) => {
+                self.name_popup.set_input(&short_name);
+                self.naming = Some(NameTarget::CheckoutAs(name, mode));
+                self.name_popup.activated = true;
+            }
+            Ok(CheckoutOutcome::Blocked(paths)) => {
+                let mut lines = vec![format!(
+                    "Checking out {} would overwrite the local changes of:",
+                    name
+                )];
+                lines.extend(
+                    paths
+                        .iter()
+                        .take(MAX_PREVIEW_LINES)
+                        .map(|path| format!("  {}", path)),
+                );
+                if paths.len() > MAX_PREVIEW_LINES {
+                    lines.push(format!(
+                        "  ... and {} more",
+                        paths.len() - MAX_PREVIEW_LINES
+                    ));
+                }
+                let options = vec![
+                    (
+                        's',
+                        "stash, switch, pop".to_string(),
+                        (target.clone(), CheckoutMode::Stash),
+                    ),
+                    (
+                        'm',
+                        "carry the changes, conflicts are left in the files".to_string(),
+                        (target, CheckoutMode::Carry),
+                    ),
+                ];
+                self.checkout_popup.ask("Checkout", lines, options);
+            }
+            Ok(CheckoutOutcome::StashKept(switched, reason, paths)) => {
+                let first = if switched {
+                    format!(
+                        "Switched to {}, but your changes could not be applied:",
+                        name
+                    )
+                } else {
+                    format!(
+                        "{} was not checked out and your changes could not be restored:",
+                        name
+                    )
+                };
+                let mut lines = vec![first, reason];
+                lines.extend(paths.into_iter().map(|path| format!("  {}", path)));
+                lines.push("they are kept in stash@{0}".to_string());
+                git.notification.show("Checkout", lines);
+            }
+            Ok(outcome) => {
+                if let CheckoutOutcome::Conflicts(paths) = outcome {
+                    let mut lines =
+                        vec![format!("Switched to {}, your changes conflict in:", name)];
+                    lines.extend(paths.into_iter().map(|path| format!("  {}", path)));
+                    if mode == CheckoutMode::Stash {
+                        lines.push("they are also kept in stash@{0}".to_string());
+                    }
+                    git.notification.show("Checkout conflicts", lines);
+                }
+            }
+            Err(e) => git
+                .notification
+                .show("Checkout failed", vec![e.message().to_string()]),
+        }
+        self.reset_branch(git);
+    }
+
+    pub fn checkout_key_event(&mut self, key_event: KeyEvent, git: &mut Git) {
+        if let Some((target, mode)) = self.checkout_popup.handle_key_event(key_event) {
+            self.checkout(target, mode, git);
+        }
+    }
+
+    /// `git branch -d`: a plain confirmation when the branch is merged in HEAD or its upstream,
+    /// typing its name when commits would only survive in the reflog
+    fn ask_delete_local(&mut self, branch_name: String, git: &mut Git) {
+        if branch_name == git.branch.current && git.branch.head == HeadState::OnBranch {
+            git.notification.show(
+                "Delete failed",
+                vec!["Cannot delete the current branch".to_string()],
+            );
+            return;
+        }
+        let unmerged = match git.branch.get_unmerged_commits(&branch_name, &git.repo) {
+            Ok(unmerged) => unmerged,
+            Err(e) => {
+                git.notification
+                    .show("Delete failed", vec![e.message().to_string()]);
+                return;
+            }
+        };
+        if unmerged.is_empty() {
+            self.confirm_popup.ask(
+                "Delete branch",
+                vec![format!(
+                    "Delete {}? Its commits are in HEAD or its upstream",
+                    branch_name
+                )],
+                BranchAction::DeleteLocal(branch_name),
+            );
+            return;
+        }
+
+        let mut lines = vec![format!(
+            "{} commit(s) of {} are merged nowhere:",
+            unmerged.len(),
+            branch_name
+        )];
+        for id in unmerged.iter().take(MAX_PREVIEW_LINES) {
+            let summary = git
+                .repo
+                .find_commit(*id)
+                .map(|commit| commit.summary().unwrap_or("").to_string())
+                .unwrap_or_default();
+            lines.push(format!("  {} {}", get_short_id(*id), summary));
+        }
+        if unmerged.len() > MAX_PREVIEW_LINES {
+            lines.push(format!(
+                "  ... and {} more",
+                unmerged.len() - MAX_PREVIEW_LINES
+            ));
+        }
+        self.confirm_popup.ask_typed(
+            "Force delete branch",
+            lines,
+            &branch_name,
+            BranchAction::DeleteLocal(branch_name.clone()),
+        );
+    }
+
+    fn delete_local(&mut self, branch_name: String, git: &mut Git) {
+        let tip = git
+            .repo
+            .find_branch(&branch_name, BranchType::Local)
+            .ok()
+            .and_then(|branch| branch.get().target());
+        match git.branch.delete_branch(&branch_name, &git.repo) {
+            Ok(()) => {
+                if let Some(tip) = tip {
+                    git.notification.show(
+                        "Branch deleted",
+                        vec![
+                            format!("Deleted {} (was {})", branch_name, get_short_id(tip)),
+                            "Ctrl+z restores it".to_string(),
+                        ],
+                    );
+                    git.record(
+                        format!("delete branch {}", branch_name),
+                        Undo::Restore(branch_name, tip),
+                    );
+                }
+            }
+            Err(e) => git
+                .notification
+                .show("Delete failed", vec![e.message().to_string()]),
+        }
+        self.reset_branch(git);
+    }
+
+    fn get_selected_local(&self, git: &Git) -> Option<String> {
+        git.branch
+            .local_branches
+            .get(self.pos_local_branches as usize)
+            .cloned()
+    }
+
+    /// reload the branches, keeping the selected ones selected when they still exist
+    pub fn reset_branch(&mut self, git: &mut Git) {
+        let local = git
+            .branch
+            .local_branches
+            .get(self.pos_local_branches as usize)
+            .cloned();
+        let remote = git
+            .branch
+            .remote_branches
+            .get(self.pos_remote_branches as usize)
+            .cloned();
         git.branch = Branch::new(&git.repo);
+        self.pos_local_branches = find_position(&git.branch.local_branches, local);
+        self.pos_remote_branches = find_position(&git.branch.remote_branches, remote);
         self.nb_local_branch = git.branch.local_branches.len() as u16;
         self.nb_remote_branch = git.branch.remote_branches.len() as u16;
     }
 
+    fn get_focused_branches<'a>(&self, git: &'a Git) -> &'a [String] {
+        match self.focused_block {
+            BranchBlock::Local => &git.branch.local_branches,
+            BranchBlock::Remote => &git.branch.remote_branches,
+        }
+    }
+
+    fn get_focused_pos(&self) -> u16 {
+        match self.focused_block {
+            BranchBlock::Local => self.pos_local_branches,
+            BranchBlock::Remote => self.pos_remote_branches,
+        }
+    }
+
+    fn set_focused_pos(&mut self, pos: u16) {
+        match self.focused_block {
+            BranchBlock::Local => self.pos_local_branches = pos,
+            BranchBlock::Remote => self.pos_remote_branches = pos,
+        }
+    }
+
     pub fn draw(&self, frame: &mut Frame, content: Rect, git: &Git) {
         let [top, bottom] =
             Layout::vertical([Constraint::Length(2), Constraint::Fill(1)]).areas(content);
@@ -103,39 +525,129 @@ impl BranchTab {
         self.draw_local_branches(frame, bottom_left, git);
         self.draw_remote_branches(frame, bottom_right, git);
         self.draw_current_branch(frame, top, git);
+
+        if self.name_popup.activated {
+            let title = match &self.naming {
+                Some(NameTarget::Rename(name)) => format!("Rename {}", name),
+                Some(NameTarget::CheckoutAs(remote_branch, _)) => format!(
+                    "A local branch has this name, check out {} as",
+                    remote_branch
+                ),
+                None => String::new(),
+            };
+            self.name_popup.draw_popup(frame, content, &title);
+        }
+        if self.confirm_popup.is_activated() {
+            self.confirm_popup.draw_popup(frame, content);
+        }
+        if self.checkout_popup.is_activated() {
+            self.checkout_popup.draw_popup(frame, content);
+        }
     }
 
     fn draw_current_branch(&self, frame: &mut Frame, area: Rect, git: &Git) {
-        let zone = Paragraph::new(format!("Current branch: {}", git.branch.current)).centered();
-        frame.render_widget(zone, area);
+        let zone = match git.branch.head {
+            HeadState::OnBranch => {
+                Paragraph::new(format!("Current branch: {}", git.branch.current))
+            }
+            HeadState::Unborn => Paragraph::new(format!(
+                "Current branch: {} (no commit yet)",
+                git.branch.current
+            )),
+            HeadState::Detached => Paragraph::new(format!(
+                "⚠ HEAD detached at {}: new commits belong to no branch, press n to create a branch here",
+                git.branch.current
+            ))
+            .style(Style::default().fg(Color::Yellow)),
+        };
+        frame.render_widget(zone.centered(), area);
     }
 
     fn draw_local_branches(&self, frame: &mut Frame, area: Rect, git: &Git) {
+        self.list_height.set(area.height.saturating_sub(2));
         let block = self.make_status_block(
             self.focused_block == BranchBlock::Local,
-            "Branches".to_string(),
+            format!("Branches ({})", git.branch.local_branches.len()),
+        );
+        let items: Vec<ListItem> = git
+            .branch
+            .local_branches
+            .iter()
+            .zip(&git.branch.local_infos)
+            .map(|(name, info)| self.make_local_item(name, info, git))
+            .collect();
+        let list = List::new(items)
+            .block(block)
+            .highlight_style(Style::default().bg(Color::DarkGray));
+        render_list(
+            frame,
+            area,
+            list,
+            self.pos_local_branches.into(),
+            git.branch.local_branches.len(),
+            self.focused_block == BranchBlock::Local,
         );
-        let paragraph = Paragraph::new(git.branch.local_branches.clone().join("\n"))
-            .centered()
-            .scroll((self.pos_local_branches, 0))
-            .block(block);
-        frame.render_widget(paragraph, area);
+    }
+
+    /// "* main [origin/main ↑1 ↓2] fix the parser · Alice · 2 days ago"
+    fn make_local_item(&self, name: &str, info: &BranchInfo, git: &Git) -> ListItem<'static> {
+        let mut spans = if name == git.branch.current && git.branch.head == HeadState::OnBranch {
+            vec![Span::raw(format!("* {}", name)).green().bold()]
+        } else {
+            vec![Span::raw(format!("  {}", name))]
+        };
+        if let Some(tracking) = info.get_tracking_text() {
+            let color = if info.gone { Color::Red } else { Color::Cyan };
+            spans.push(Span::styled(
+                format!(" [{}]", tracking),
+                Style::default().fg(color),
+            ));
+        }
+        if let Some(commit) = &info.last_commit {
+            spans.push(Span::raw(format!(" {}", commit.summary)));
+            spans.push(
+                Span::raw(format!(
+                    " · {} · {}",
+                    commit.author,
+                    get_relative_time(commit.time)
+                ))
+                .dark_gray(),
+            );
+        }
+        ListItem::new(Line::from(spans))
     }
 
     fn draw_remote_branches(&self, frame: &mut Frame, area: Rect, git: &Git) {
         let block = self.make_status_block(
             self.focused_block == BranchBlock::Remote,
-            "Remote".to_string(),
+            format!("Remote ({})", git.branch.remote_branches.len()),
+        );
+        let items: Vec<ListItem> = git
+            .branch
+            .remote_branches
+            .iter()
+            .map(|name| ListItem::new(format!("  {}", name)))
+            .collect();
+        let list = List::new(items)
+            .block(block)
+            .highlight_style(Style::default().bg(Color::DarkGray));
+        render_list(
+            frame,
+            area,
+            list,
+            self.pos_remote_branches.into(),
+            git.branch.remote_branches.len(),
+            self.focused_block == BranchBlock::Remote,
         );
-
-        let paragraph = Paragraph::new(git.branch.remote_branches.clone().join("\n"))
-            .centered()
-            .scroll((self.pos_remote_branches, 0))
-            .block(block);
-        frame.render_widget(paragraph, area);
     }
 }
 
+/// position of `name` in `branches`, the top when it is gone
+fn find_position(branches: &[String], name: Option<String>) -> u16 {
+    name.and_then(|name| branches.iter().position(|branch| *branch == name))
+        .unwrap_or(0) as u16
+}
+
 impl Default for BranchTab {
     fn default() -> Self {
         BranchTab::new()
@@ -173,6 +685,21 @@ impl Move for BranchTab {
         }
     }
 
+    fn get_block_name(&self) -> Option<&'static str> {
+        Some(match self.focused_block {
+            BranchBlock::Local => "local",
+            BranchBlock::Remote => "remote",
+        })
+    }
+
+    fn focus_block(&mut self, name: &str) {
+        self.focused_block = match name {
+            "local" => BranchBlock::Local,
+            "remote" => BranchBlock::Remote,
+            _ => return,
+        };
+    }
+
     fn change_block(&mut self, code: KeyCode) {
         if !DIRECTION.contains(&code) {
             return;
@@ -184,3 +711,88 @@ impl Move for BranchTab {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use git2::{Repository, Signature};
+    use std::{env, fs};
+
+    fn press(tab: &mut BranchTab, git: &mut Git, keys: &str) {
+        for key in keys.chars() {
+            let code = match key {
+                '\n' => KeyCode::Enter,
+                '\u{8}' => KeyCode::Backspace,
+                key => KeyCode::Char(key),
+            };
+            tab.confirm_key_event(KeyEvent::new(code, KeyModifiers::NONE), git);
+        }
+    }
+
+    /// ask to delete `name` from the Local block, like `d` on it
+    fn ask_delete(tab: &mut BranchTab, git: &mut Git, name: &str) {
+        tab.reset_branch(git);
+        tab.pos_local_branches = git
+            .branch
+            .local_branches
+            .iter()
+            .position(|branch| branch == name)
+            .unwrap() as u16;
+        tab.run_action("delete", git);
+    }
+
+    #[test]
+    fn delete_names_the_short_id_and_forced_delete_needs_the_name() {
+        let workdir = env::temp_dir().join(format!("git-ui-delete-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&workdir);
+        let repo = Repository::init(&workdir).unwrap();
+        let signature = Signature::now("test", "test@example.com").unwrap();
+        let tree = repo
+            .find_tree(repo.index().unwrap().write_tree().unwrap())
+            .unwrap();
+        let base = repo
+            .commit(Some("HEAD"), &signature, &signature, "base", &tree, &[])
+            .unwrap();
+        let base_commit = repo.find_commit(base).unwrap();
+        repo.branch("merged", &base_commit, false).unwrap();
+        let ahead = repo
+            .commit(None, &signature, &signature, "wip", &tree, &[&base_commit])
+            .unwrap();
+        repo.branch("unmerged", &repo.find_commit(ahead).unwrap(), false)
+            .unwrap();
+        drop((tree, base_commit));
+        let mut git = Git::new(repo);
+        let mut tab = BranchTab::new();
+
+        ask_delete(&mut tab, &mut git, "merged");
+        assert!(tab.confirm_popup.expected.is_none());
+        press(&mut tab, &mut git, "y");
+        assert!(git.repo.find_branch("merged", BranchType::Local).is_err());
+        assert_eq!(
+            git.notification.lines[0],
+            format!("Deleted merged (was {})", get_short_id(base))
+        );
+
+        // its commit is merged nowhere: the name has to be typed
+        ask_delete(&mut tab, &mut git, "unmerged");
+        assert_eq!(tab.confirm_popup.expected.as_deref(), Some("unmerged"));
+        assert_eq!(
+            tab.confirm_popup.lines[1],
+            format!("  {} wip", get_short_id(ahead))
+        );
+        press(&mut tab, &mut git, "y\n");
+        assert!(git.repo.find_branch("unmerged", BranchType::Local).is_ok());
+        press(&mut tab, &mut git, "\u{8}unmerged\n");
+        assert!(git.repo.find_branch("unmerged", BranchType::Local).is_err());
+        assert_eq!(
+            git.notification.lines[0],
+            format!("Deleted unmerged (was {})", get_short_id(ahead))
+        );
+
+        // Ctrl+z puts the last one back on its tip
+        git.undo_last().unwrap();
+        let restored = git.repo.refname_to_id("refs/heads/unmerged").unwrap();
+        assert_eq!(restored, ahead);
+        fs::remove_dir_all(&workdir).unwrap();
+    }
+}
@@ -0,0 +1,353 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use git2::ConfigLevel;
+use ratatui::{
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Style, Stylize},
+    widgets::{List, ListItem, Paragraph},
+    Frame,
+};
+use std::cell::Cell;
+
+use crate::{
+    git::{get_level_name, ConfigEntry, Git, GitConfig, CONFIG_LEVELS},
+    popup::{Choice, Confirm, Popup},
+    tabs::{
+        mover::Move,
+        selection::{get_jump, render_list},
+    },
+};
+
+/// settings git-ui needs (commit signature) or that change how git behaves, shown even unset
+const SUGGESTED_KEYS: [&str; 4] = ["user.name", "user.email", "pull.rebase", "push.default"];
+
+pub enum ConfigRow {
+    Header(String),
+    Entry(usize),
+    Missing(&'static str),
+}
+
+pub enum ConfigAction {
+    Add(ConfigLevel),
+    SetMissing(ConfigLevel, String),
+}
+
+enum EditTarget {
+    Value(ConfigLevel, String),
+    NewEntry(ConfigLevel),
+}
+
+pub struct ConfigTab {
+    pub pos: u16,
+    pub entries: Vec<ConfigEntry>,
+    pub rows: Vec<ConfigRow>,
+    pub edit_popup: Popup,
+    editing: Option<EditTarget>,
+    pub level_popup: Choice<ConfigAction>,
+    pub confirm_popup: Confirm<(ConfigLevel, String)>,
+    /// rows visible in the list at the last frame, for PageUp / PageDown
+    list_height: Cell<u16>,
+}
+
+impl ConfigTab {
+    pub fn new() -> Self {
+        ConfigTab {
+            pos: 0,
+            entries: Vec::new(),
+            rows: Vec::new(),
+            edit_popup: Popup::new(),
+            editing: None,
+            level_popup: Choice::new(),
+            confirm_popup: Confirm::new(),
+            list_height: Cell::new(0),
+        }
+    }
+
+    pub fn refresh(&mut self, git: &mut Git) {
+        self.entries = match git.get_config_entries() {
+            Ok(entries) => entries,
+            Err(e) => {
+                git.notification
+                    .show("Config", vec![e.message().to_string()]);
+                Vec::new()
+            }
+        };
+
+        self.rows = Vec::new();
+        for level in CONFIG_LEVELS {
+            let indexes: Vec<usize> = (0..self.entries.len())
+                .filter(|i| self.entries[*i].level == level)
+                .collect();
+            if indexes.is_empty() {
+                continue;
+            }
+            let path = git
+                .get_config_path(level)
+                .map(|path| format!(" ({})", path.display()))
+                .unwrap_or_default();
+            self.rows.push(ConfigRow::Header(format!(
+                "{}{}",
+                get_level_name(level),
+                path
+            )));
+            self.rows.extend(indexes.into_iter().map(ConfigRow::Entry));
+        }
+        let missing: Vec<&'static str> = SUGGESTED_KEYS
+            .into_iter()
+            .filter(|key| !self.entries.iter().any(|entry| entry.name == *key))
+            .collect();
+        if !missing.is_empty() {
+            self.rows.push(ConfigRow::Header("not set".to_string()));
+            self.rows
+                .extend(missing.into_iter().map(ConfigRow::Missing));
+        }
+
+        if self.pos as usize >= self.rows.len() {
+            self.pos = self.rows.len().saturating_sub(1) as u16;
+        }
+    }
+
+    pub fn handle_key_event(&mut self, key_event: KeyEvent) {
+        match key_event.code {
+            KeyCode::Up => self.scroll_up(),
+            KeyCode::Down => self.scroll_down(),
+            KeyCode::PageUp | KeyCode::PageDown | KeyCode::Home | KeyCode::End => {
+                let height = self.list_height.get() as usize;
+                let pos = self.pos as usize;
+                if let Some(pos) = get_jump(key_event.code, pos, self.rows.len(), height) {
+                    self.pos = pos as u16;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// run the action called `name` (see `keymap::get_actions`) on the selected entry
+    pub fn run_action(&mut self, name: &str) {
+        match name {
+            "edit" => match self.rows.get(self.pos as usize) {
+                Some(ConfigRow::Entry(index)) => {
+                    let entry = &self.entries[*index];
+                    self.edit_popup.set_input(&entry.value);
+                    self.editing = Some(EditTarget::Value(entry.level, entry.name.clone()));
+                    self.edit_popup.activated = true;
+                }
+                Some(&ConfigRow::Missing(name)) => self
+                    .ask_level(&format!("Set {} in", name), |level| {
+                        ConfigAction::SetMissing(level, name.to_string())
+                    }),
+                _ => {}
+            },
+            "add" => self.ask_level("Add an entry to", ConfigAction::Add),
+            "delete" => {
+                if let Some(ConfigRow::Entry(index)) = self.rows.get(self.pos as usize) {
+                    let entry = &self.entries[*index];
+                    self.confirm_popup.ask(
+                        "Remove",
+                        vec![format!(
+                            "Remove {} = {} from the {} configuration",
+                            entry.name,
+                            entry.value,
+                            get_level_name(entry.level)
+                        )],
+                        (entry.level, entry.name.clone()),
+                    );
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// configuration files a user writes to: the repository one and their own
+    fn ask_level(&mut self, title: &str, action: impl Fn(ConfigLevel) -> ConfigAction) {
+        let options = vec![
+            (
+                'l',
+                "local (this repository)".to_string(),
+                action(ConfigLevel::Local),
+            ),
+            (
+                'g',
+                "global (every repository)".to_string(),
+                action(ConfigLevel::Global),
+            ),
+        ];
+        self.level_popup
+            .ask("Config", vec![title.to_string()], options);
+    }
+
+    pub fn level_key_event(&mut self, key_event: KeyEvent) {
+        match self.level_popup.handle_key_event(key_event) {
+            Some(ConfigAction::Add(level)) => {
+                self.edit_popup.set_input("");
+                self.editing = Some(EditTarget::NewEntry(level));
+                self.edit_popup.activated = true;
+            }
+            Some(ConfigAction::SetMissing(level, name)) => {
+                self.edit_popup.set_input("");
+                self.editing = Some(EditTarget::Value(level, name));
+                self.edit_popup.activated = true;
+            }
+            None => {}
+        }
+    }
+
+    pub fn edit_key_event(&mut self, key_event: KeyEvent, git: &mut Git) {
+        match key_event.code {
+            KeyCode::Esc => {
+                self.edit_popup.activated = false;
+                self.editing = None;
+            }
+            KeyCode::Char(to_insert) => self.edit_popup.enter_char(to_insert),
+            KeyCode::Left => self.edit_popup.move_cursor_left(),
+            KeyCode::Right => self.edit_popup.move_cursor_right(),
+            KeyCode::Backspace => self.edit_popup.delete_char(),
+            KeyCode::Enter => {
+                let result = match self.editing.take() {
+                    Some(EditTarget::Value(level, name)) => {
+                        git.set_config(level, &name, &self.edit_popup.input)
+                    }
+                    Some(EditTarget::NewEntry(level)) => {
+                        match self.edit_popup.input.split_once('=') {
+                            Some((name, value)) => git.set_config(level, name.trim(), value.trim()),
+                            None => Err(git2::Error::from_str("Expected name = value")),
+                        }
+                    }
+                    None => Ok(()),
+                };
+                if let Err(e) = result {
+                    git.notification
+                        .show("Config", vec![e.message().to_string()]);
+                }
+                self.edit_popup.activated = false;
+                self.refresh(git);
+            }
+            _ => {}
+        }
+    }
+
+    pub fn confirm_key_event(&mut self, key_event: KeyEvent, git: &mut Git) {
+        if let Some((level, name)) = self.confirm_popup.handle_key_event(key_event) {
+            if let Err(e) = git.remove_config(level, &name) {
+                git.notification
+                    .show("Config", vec![e.message().to_string()]);
+            }
+            self.refresh(git);
+        }
+    }
+
+    pub fn draw(&self, frame: &mut Frame, content: Rect) {
+        let [list_area, details_area] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(3)]).areas(content);
+
+        let items: Vec<ListItem> = if self.rows.is_empty() {
+            vec![ListItem::new("No configuration")]
+        } else {
+            self.rows
+                .iter()
+                .map(|row| self.make_row_item(row))
+                .collect()
+        };
+        let list = List::new(items)
+            .block(self.make_status_block(true, "Git configuration".to_string()))
+            .highlight_style(Style::default().bg(Color::DarkGray));
+        self.list_height.set(list_area.height.saturating_sub(2));
+        render_list(
+            frame,
+            list_area,
+            list,
+            self.pos as usize,
+            self.rows.len(),
+            true,
+        );
+
+        let details = Paragraph::new(self.get_details())
+            .block(self.make_status_block(false, "Details".to_string()));
+        frame.render_widget(details, details_area);
+
+        if self.edit_popup.activated {
+            let title = match &self.editing {
+                Some(EditTarget::Value(level, name)) => {
+                    format!("{} ({})", name, get_level_name(*level))
+                }
+                Some(EditTarget::NewEntry(level)) => {
+                    format!("New {} entry: name = value", get_level_name(*level))
+                }
+                None => String::new(),
+            };
+            self.edit_popup.draw_popup(frame, content, &title);
+        }
+        if self.level_popup.is_activated() {
+            self.level_popup.draw_popup(frame, content);
+        }
+        if self.confirm_popup.is_activated() {
+            self.confirm_popup.draw_popup(frame, content);
+        }
+    }
+
+    fn make_row_item(&self, row: &ConfigRow) -> ListItem<'static> {
+        match row {
+            ConfigRow::Header(title) => ListItem::new(format!("── {} ──", title)).cyan().bold(),
+            ConfigRow::Entry(index) => {
+                let entry = &self.entries[*index];
+                match entry.overridden_by {
+                    Some(level) => ListItem::new(format!(
+                        "  {} = {} (overridden by {})",
+                        entry.name,
+                        entry.value,
+                        get_level_name(level)
+                    ))
+                    .dark_gray(),
+                    None => ListItem::new(format!("  {} = {}", entry.name, entry.value)),
+                }
+            }
+            ConfigRow::Missing(name) => ListItem::new(format!("  {}", name)).yellow(),
+        }
+    }
+
+    /// where the value of the selected key comes from
+    fn get_details(&self) -> String {
+        match self.rows.get(self.pos as usize) {
+            Some(ConfigRow::Entry(index)) => {
+                let entry = &self.entries[*index];
+                match entry.overridden_by {
+                    Some(level) => format!(
+                        "{} from {} is not used, the {} value wins",
+                        entry.name,
+                        get_level_name(entry.level),
+                        get_level_name(level)
+                    ),
+                    None => format!(
+                        "{} = {} comes from the {} configuration",
+                        entry.name,
+                        entry.value,
+                        get_level_name(entry.level)
+                    ),
+                }
+            }
+            Some(ConfigRow::Missing(name)) => format!("{} is not set, e to set it", name),
+            _ => "e: edit / a: add / d: remove".to_string(),
+        }
+    }
+}
+
+impl Default for ConfigTab {
+    fn default() -> Self {
+        ConfigTab::new()
+    }
+}
+
+impl Move for ConfigTab {
+    fn scroll_up(&mut self) {
+        if self.pos > 0 {
+            self.pos -= 1;
+        }
+    }
+
+    fn scroll_down(&mut self) {
+        if (self.pos as usize) + 1 < self.rows.len() {
+            self.pos += 1;
+        }
+    }
+
+    fn change_block(&mut self, _code: KeyCode) {}
+}
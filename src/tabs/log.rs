@@ -0,0 +1,279 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use git2::{Oid, ResetType};
+use ratatui::{
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Style},
+    widgets::{List, ListItem, Paragraph, Wrap},
+    Frame,
+};
+use std::cell::Cell;
+
+use crate::{
+    config::Config,
+    git::{get_commits, CherryPick, CommitInfo, Git, HeadState, Reset},
+    pages::Pages,
+    popup::Confirm,
+    tabs::{
+        mover::Move,
+        selection::{get_jump, render_list},
+    },
+};
+
+const MAX_COMMITS: usize = 500;
+
+pub enum LogAction {
+    Reset(Oid, ResetType),
+}
+
+pub struct LogTab {
+    pub pos_commit: u16,
+    pub commits: Vec<CommitInfo>,
+    pub reference: String,
+    pub range_start: Option<u16>,
+    pub add_trailer: bool,
+    pub confirm_popup: Confirm<LogAction>,
+    /// rows visible in the list at the last frame, for PageUp / PageDown
+    list_height: Cell<u16>,
+}
+
+impl LogTab {
+    pub fn new() -> Self {
+        LogTab {
+            pos_commit: 0,
+            commits: Vec::new(),
+            reference: String::from("HEAD"),
+            range_start: None,
+            add_trailer: false,
+            confirm_popup: Confirm::new(),
+            list_height: Cell::new(0),
+        }
+    }
+
+    /// show the history of `reference` (branch name or "HEAD")
+    pub fn open(&mut self, reference: &str, git: &mut Git) {
+        self.reference = reference.to_string();
+        self.pos_commit = 0;
+        self.range_start = None;
+        self.refresh(git);
+    }
+
+    pub fn refresh(&mut self, git: &mut Git) {
+        if self.reference == "HEAD" && git.branch.head == HeadState::Unborn {
+            self.commits = Vec::new();
+            self.pos_commit = 0;
+            return;
+        }
+        self.commits = match get_commits(&git.repo, &self.reference, MAX_COMMITS) {
+            Ok(commits) => commits,
+            Err(e) => {
+                git.notification
+                    .show("History", vec![e.message().to_string()]);
+                Vec::new()
+            }
+        };
+        if self.pos_commit as usize >= self.commits.len() {
+            self.pos_commit = self.commits.len().saturating_sub(1) as u16;
+        }
+    }
+
+    pub fn handle_key_event(&mut self, key_event: KeyEvent) {
+        if key_event.modifiers == KeyModifiers::CONTROL {
+            self.change_block(key_event.code);
+            return;
+        }
+        if self.commits.is_empty() {
+            return;
+        }
+        match key_event.code {
+            KeyCode::Up => self.scroll_up(),
+            KeyCode::Down => self.scroll_down(),
+            KeyCode::PageUp | KeyCode::PageDown | KeyCode::Home | KeyCode::End => {
+                let height = self.list_height.get() as usize;
+                let pos = self.pos_commit as usize;
+                if let Some(pos) = get_jump(key_event.code, pos, self.commits.len(), height) {
+                    self.pos_commit = pos as u16;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// run the action called `name` (see `keymap::get_actions`) on the selected commits
+    pub fn run_action(&mut self, name: &str, git: &mut Git) {
+        if self.commits.is_empty() {
+            return;
+        }
+        match name {
+            "range" => {
+                self.range_start = match self.range_start {
+                    Some(_) => None,
+                    None => Some(self.pos_commit),
+                }
+            }
+            "trailer" => self.add_trailer = !self.add_trailer,
+            "cherry_pick" => {
+                let ids = self.get_selected_ids();
+                if let Err(e) = git.cherry_pick(&ids, self.add_trailer) {
+                    git.notification
+                        .show("Cherry-pick failed", vec![e.message().to_string()]);
+                }
+                self.range_start = None;
+                self.refresh(git);
+            }
+            "revert" => {
+                let id = self.commits[self.pos_commit as usize].id;
+                if let Err(e) = git.revert_commit(id) {
+                    git.notification
+                        .show("Revert failed", vec![e.message().to_string()]);
+                }
+                self.refresh(git);
+            }
+            "reset_soft" => self.ask_reset(ResetType::Soft, git),
+            "reset_mixed" => self.ask_reset(ResetType::Mixed, git),
+            "reset_hard" => self.ask_reset(ResetType::Hard, git),
+            _ => {}
+        }
+    }
+
+    fn ask_reset(&mut self, kind: ResetType, git: &mut Git) {
+        let commit = &self.commits[self.pos_commit as usize];
+        let name = match kind {
+            ResetType::Soft => "Soft reset",
+            ResetType::Mixed => "Mixed reset",
+            ResetType::Hard => "Hard reset",
+        };
+        match git.get_reset_preview(commit.id, kind) {
+            Ok(mut lines) => {
+                lines.insert(0, format!("Reset HEAD to {} {}", commit.short_id, commit.summary));
+                self.confirm_popup
+                    .ask(name, lines, LogAction::Reset(commit.id, kind));
+            }
+            Err(e) => git.notification.show(name, vec![e.message().to_string()]),
+        }
+    }
+
+    pub fn confirm_key_event(&mut self, key_event: KeyEvent, git: &mut Git) {
+        let result = match self.confirm_popup.handle_key_event(key_event) {
+            Some(LogAction::Reset(id, kind)) => git.reset_head(id, kind),
+            None => return,
+        };
+        if let Err(e) = result {
+            git.notification
+                .show("Reset failed", vec![e.message().to_string()]);
+        }
+        self.refresh(git);
+    }
+
+    /// selected commits ordered from the oldest to the newest
+    fn get_selected_ids(&self) -> Vec<Oid> {
+        let (first, last) = self.get_range();
+        self.commits[first..=last]
+            .iter()
+            .rev()
+            .map(|commit| commit.id)
+            .collect()
+    }
+
+    fn get_range(&self) -> (usize, usize) {
+        let pos = self.pos_commit as usize;
+        match self.range_start {
+            Some(start) => (pos.min(start as usize), pos.max(start as usize)),
+            None => (pos, pos),
+        }
+    }
+
+    pub fn draw(&self, frame: &mut Frame, content: Rect, config: &Config) {
+        let [top, bottom] =
+            Layout::vertical([Constraint::Length(2), Constraint::Fill(1)]).areas(content);
+        let [bottom_left, bottom_right] =
+            Layout::horizontal([Constraint::Percentage(60), Constraint::Fill(1)]).areas(bottom);
+
+        let header = format!(
+            "History of {} | -x trailer ({}): {} | range ({}): {}",
+            self.reference,
+            config.get_action_key_name(&Pages::LogPAGE, "trailer"),
+            if self.add_trailer { "on" } else { "off" },
+            config.get_action_key_name(&Pages::LogPAGE, "range"),
+            if self.range_start.is_some() { "on" } else { "off" },
+        );
+        frame.render_widget(Paragraph::new(header).centered(), top);
+
+        self.draw_commits(frame, bottom_left);
+        self.draw_details(frame, bottom_right);
+        if self.confirm_popup.is_activated() {
+            self.confirm_popup.draw_popup(frame, content);
+        }
+    }
+
+    fn draw_commits(&self, frame: &mut Frame, area: Rect) {
+        self.list_height.set(area.height.saturating_sub(2));
+        let (first, last) = self.get_range();
+        let items: Vec<ListItem> = if self.commits.is_empty() {
+            vec![ListItem::new("No commits")]
+        } else {
+            self.commits
+                .iter()
+                .enumerate()
+                .map(|(i, commit)| {
+                    let item = ListItem::new(format!(
+                        "{} {} ({})",
+                        commit.short_id, commit.summary, commit.author
+                    ));
+                    if self.range_start.is_some() && i >= first && i <= last {
+                        item.style(Style::default().fg(Color::Yellow))
+                    } else {
+                        item
+                    }
+                })
+                .collect()
+        };
+
+        let list = List::new(items)
+            .block(self.make_status_block(true, format!("Commits ({})", self.commits.len())))
+            .highlight_style(Style::default().bg(Color::DarkGray));
+        render_list(
+            frame,
+            area,
+            list,
+            self.pos_commit as usize,
+            self.commits.len(),
+            true,
+        );
+    }
+
+    fn draw_details(&self, frame: &mut Frame, area: Rect) {
+        let text = match self.commits.get(self.pos_commit as usize) {
+            Some(commit) => format!(
+                "commit {}\nAuthor: {}\n\n{}",
+                commit.id, commit.author, commit.message
+            ),
+            None => String::new(),
+        };
+        let details = Paragraph::new(text)
+            .wrap(Wrap { trim: false })
+            .block(self.make_status_block(false, "Details".to_string()));
+        frame.render_widget(details, area);
+    }
+}
+
+impl Default for LogTab {
+    fn default() -> Self {
+        LogTab::new()
+    }
+}
+
+impl Move for LogTab {
+    fn scroll_up(&mut self) {
+        if self.pos_commit > 0 {
+            self.pos_commit -= 1;
+        }
+    }
+
+    fn scroll_down(&mut self) {
+        if (self.pos_commit as usize) + 1 < self.commits.len() {
+            self.pos_commit += 1;
+        }
+    }
+
+    fn change_block(&mut self, _code: KeyCode) {}
+}
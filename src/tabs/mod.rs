@@ -1,6 +1,17 @@
 mod branch;
+mod config;
+mod log;
 mod mover;
+mod reflog;
+mod selection;
+mod stash;
 mod status;
+mod tree;
 
 pub use branch::BranchTab;
+pub use config::ConfigTab;
+pub use log::LogTab;
+pub use mover::Move;
+pub use reflog::ReflogTab;
+pub use stash::StashTab;
 pub use status::StatusTab;
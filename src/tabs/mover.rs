@@ -10,7 +10,15 @@ pub trait Move {
 
     fn change_block(&mut self, code: KeyCode);
 
-    fn make_status_block(&self, focus: bool, title: String) -> Block {
+    /// name of the focused block, tells the keymap which actions apply
+    fn get_block_name(&self) -> Option<&'static str> {
+        None
+    }
+
+    /// focus the block named `name` by `get_block_name`
+    fn focus_block(&mut self, _name: &str) {}
+
+    fn make_status_block(&self, focus: bool, title: String) -> Block<'_> {
         if focus {
             Block::bordered().title(title).bold()
         } else {
@@ -0,0 +1,304 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use git2::{Oid, ResetType};
+use ratatui::{
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Style},
+    widgets::{List, ListItem},
+    Frame,
+};
+use std::cell::Cell;
+
+use crate::{
+    git::{get_reflog, get_reflog_refs, get_short_id, Git, ReflogEntry, Reset, RestoreRef},
+    popup::Confirm,
+    tabs::{
+        mover::{Move, DIRECTION},
+        selection::{get_jump, render_list},
+    },
+};
+
+#[derive(PartialEq)]
+pub enum ReflogBlock {
+    Refs,
+    Entries,
+}
+
+pub struct ReflogTab {
+    pub pos_ref: u16,
+    pub pos_entry: u16,
+    pub refs: Vec<String>,
+    pub entries: Vec<ReflogEntry>,
+    pub focused_block: ReflogBlock,
+    pub confirm_popup: Confirm<(String, Oid)>,
+    /// rows visible in each block at the last frame, for PageUp / PageDown
+    refs_height: Cell<u16>,
+    entries_height: Cell<u16>,
+}
+
+impl ReflogTab {
+    pub fn new() -> Self {
+        ReflogTab {
+            pos_ref: 0,
+            pos_entry: 0,
+            refs: Vec::new(),
+            entries: Vec::new(),
+            focused_block: ReflogBlock::Refs,
+            confirm_popup: Confirm::new(),
+            refs_height: Cell::new(0),
+            entries_height: Cell::new(0),
+        }
+    }
+
+    pub fn refresh(&mut self, git: &mut Git) {
+        self.refs = get_reflog_refs(&git.repo).unwrap_or_else(|_e| vec!["HEAD".to_string()]);
+        if self.pos_ref as usize >= self.refs.len() {
+            self.pos_ref = 0;
+        }
+        self.load_entries(git);
+    }
+
+    fn load_entries(&mut self, git: &mut Git) {
+        self.entries = match get_reflog(&git.repo, &self.refs[self.pos_ref as usize]) {
+            Ok(entries) => entries,
+            Err(e) => {
+                git.notification
+                    .show("Reflog", vec![e.message().to_string()]);
+                Vec::new()
+            }
+        };
+        if self.pos_entry as usize >= self.entries.len() {
+            self.pos_entry = 0;
+        }
+    }
+
+    pub fn handle_key_event(&mut self, key_event: KeyEvent, git: &mut Git) {
+        if key_event.modifiers == KeyModifiers::CONTROL {
+            self.change_block(key_event.code);
+            return;
+        }
+        let previous_ref = self.pos_ref;
+        match key_event.code {
+            KeyCode::Up => self.scroll_up(),
+            KeyCode::Down => self.scroll_down(),
+            KeyCode::PageUp | KeyCode::PageDown | KeyCode::Home | KeyCode::End => {
+                let (pos, len, height) = match self.focused_block {
+                    ReflogBlock::Refs => (self.pos_ref, self.refs.len(), &self.refs_height),
+                    ReflogBlock::Entries => {
+                        (self.pos_entry, self.entries.len(), &self.entries_height)
+                    }
+                };
+                let height = height.get() as usize;
+                if let Some(pos) = get_jump(key_event.code, pos as usize, len, height) {
+                    match self.focused_block {
+                        ReflogBlock::Refs => self.pos_ref = pos as u16,
+                        ReflogBlock::Entries => self.pos_entry = pos as u16,
+                    }
+                }
+            }
+            _ => {}
+        }
+        if self.pos_ref != previous_ref {
+            self.pos_entry = 0;
+            self.load_entries(git);
+        }
+    }
+
+    /// run the action called `name` (see `keymap::get_actions`) on the selected entry
+    pub fn run_action(&mut self, name: &str, git: &mut Git) {
+        if name == "restore" {
+            self.ask_restore(git);
+        }
+    }
+
+    fn ask_restore(&mut self, git: &mut Git) {
+        let Some(entry) = self.entries.get(self.pos_entry as usize) else {
+            return;
+        };
+        let refname = self.refs[self.pos_ref as usize].clone();
+        let short_id = get_short_id(entry.new_id);
+
+        let head_name = git
+            .repo
+            .head()
+            .ok()
+            .and_then(|head| head.name().map(String::from));
+        let mut lines = vec![format!(
+            "Move {} to {} ({})",
+            refname, short_id, entry.message
+        )];
+        if refname == "HEAD" || head_name.as_deref() == Some(refname.as_str()) {
+            match git.get_reset_preview(entry.new_id, ResetType::Hard) {
+                Ok(preview) => lines.extend(preview),
+                Err(e) => lines.push(e.message().to_string()),
+            }
+        }
+        self.confirm_popup
+            .ask("Restore from reflog", lines, (refname, entry.new_id));
+    }
+
+    pub fn confirm_key_event(&mut self, key_event: KeyEvent, git: &mut Git) {
+        if let Some((refname, id)) = self.confirm_popup.handle_key_event(key_event) {
+            if let Err(e) = git.restore_ref(&refname, id) {
+                git.notification
+                    .show("Restore failed", vec![e.message().to_string()]);
+            }
+            self.refresh(git);
+        }
+    }
+
+    pub fn draw(&self, frame: &mut Frame, content: Rect, git: &Git) {
+        let [left, right] =
+            Layout::horizontal([Constraint::Percentage(35), Constraint::Fill(1)]).areas(content);
+        let [top_left, bottom_left] = Layout::vertical([Constraint::Fill(1); 2]).areas(left);
+
+        self.draw_refs(frame, top_left);
+        self.draw_journal(frame, bottom_left, git);
+        self.draw_entries(frame, right);
+        if self.confirm_popup.is_activated() {
+            self.confirm_popup.draw_popup(frame, content);
+        }
+    }
+
+    fn draw_refs(&self, frame: &mut Frame, area: Rect) {
+        self.refs_height.set(area.height.saturating_sub(2));
+        let items: Vec<ListItem> = self
+            .refs
+            .iter()
+            .map(|refname| ListItem::new(refname.as_str()))
+            .collect();
+        let list = List::new(items)
+            .block(
+                self.make_status_block(self.focused_block == ReflogBlock::Refs, "Refs".to_string()),
+            )
+            .highlight_style(Style::default().bg(Color::DarkGray));
+        // the selected ref stays highlighted: it is the one whose entries are shown
+        render_list(
+            frame,
+            area,
+            list,
+            self.pos_ref as usize,
+            self.refs.len(),
+            true,
+        );
+    }
+
+    fn draw_entries(&self, frame: &mut Frame, area: Rect) {
+        self.entries_height.set(area.height.saturating_sub(2));
+        let refname = self
+            .refs
+            .get(self.pos_ref as usize)
+            .map(|refname| refname.strip_prefix("refs/heads/").unwrap_or(refname))
+            .unwrap_or("HEAD");
+        let items: Vec<ListItem> = if self.entries.is_empty() {
+            vec![ListItem::new("No reflog entries")]
+        } else {
+            self.entries
+                .iter()
+                .enumerate()
+                .map(|(i, entry)| {
+                    ListItem::new(format!(
+                        "{} {}@{{{}}} {} ({})",
+                        get_short_id(entry.new_id),
+                        refname,
+                        i,
+                        entry.message,
+                        entry.committer
+                    ))
+                })
+                .collect()
+        };
+        let list = List::new(items)
+            .block(self.make_status_block(
+                self.focused_block == ReflogBlock::Entries,
+                format!("Reflog of {}", refname),
+            ))
+            .highlight_style(Style::default().bg(Color::DarkGray));
+        render_list(
+            frame,
+            area,
+            list,
+            self.pos_entry as usize,
+            self.entries.len(),
+            self.focused_block == ReflogBlock::Entries,
+        );
+    }
+
+    fn draw_journal(&self, frame: &mut Frame, area: Rect, git: &Git) {
+        let items: Vec<ListItem> = if git.journal.is_empty() {
+            vec![ListItem::new("No operation recorded")]
+        } else {
+            git.journal
+                .iter()
+                .rev()
+                .map(|operation| ListItem::new(operation.description.as_str()))
+                .collect()
+        };
+        let list = List::new(items)
+            .block(self.make_status_block(false, "Branch journal (ctrl+z to undo)".to_string()));
+        frame.render_widget(list, area);
+    }
+}
+
+impl Default for ReflogTab {
+    fn default() -> Self {
+        ReflogTab::new()
+    }
+}
+
+impl Move for ReflogTab {
+    fn scroll_up(&mut self) {
+        match self.focused_block {
+            ReflogBlock::Refs => {
+                if self.pos_ref > 0 {
+                    self.pos_ref -= 1;
+                }
+            }
+            ReflogBlock::Entries => {
+                if self.pos_entry > 0 {
+                    self.pos_entry -= 1;
+                }
+            }
+        }
+    }
+
+    fn scroll_down(&mut self) {
+        match self.focused_block {
+            ReflogBlock::Refs => {
+                if (self.pos_ref as usize) + 1 < self.refs.len() {
+                    self.pos_ref += 1;
+                }
+            }
+            ReflogBlock::Entries => {
+                if (self.pos_entry as usize) + 1 < self.entries.len() {
+                    self.pos_entry += 1;
+                }
+            }
+        }
+    }
+
+    fn get_block_name(&self) -> Option<&'static str> {
+        Some(match self.focused_block {
+            ReflogBlock::Refs => "refs",
+            ReflogBlock::Entries => "entries",
+        })
+    }
+
+    fn focus_block(&mut self, name: &str) {
+        self.focused_block = match name {
+            "refs" => ReflogBlock::Refs,
+            "entries" => ReflogBlock::Entries,
+            _ => return,
+        };
+    }
+
+    fn change_block(&mut self, code: KeyCode) {
+        if !DIRECTION.contains(&code) {
+            return;
+        }
+        match code {
+            KeyCode::Left => self.focused_block = ReflogBlock::Refs,
+            KeyCode::Right => self.focused_block = ReflogBlock::Entries,
+            _ => {}
+        }
+    }
+}
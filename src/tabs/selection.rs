@@ -0,0 +1,89 @@
+use crossterm::event::KeyCode;
+use ratatui::{
+    layout::{Margin, Rect},
+    widgets::{List, ListState, Scrollbar, ScrollbarOrientation, ScrollbarState},
+    Frame,
+};
+
+/// first row shown so that the selected one stays in the middle of the `height` visible rows
+pub fn get_offset(pos: usize, len: usize, height: usize) -> usize {
+    if len <= height {
+        return 0;
+    }
+    pos.saturating_sub(height / 2).min(len - height)
+}
+
+/// `list` of `len` rows scrolled around `pos`, highlighted when the block has the focus,
+/// with a scrollbar on the border when it does not fit
+pub fn render_list(
+    frame: &mut Frame,
+    area: Rect,
+    list: List,
+    pos: usize,
+    len: usize,
+    focused: bool,
+) {
+    let height = area.height.saturating_sub(2) as usize;
+    let offset = get_offset(pos, len, height);
+    let mut state = ListState::default()
+        .with_offset(offset)
+        .with_selected((focused && len > 0).then_some(pos));
+    frame.render_stateful_widget(list, area, &mut state);
+
+    if len > height {
+        let mut scrollbar_state = ScrollbarState::new(len - height).position(offset);
+        frame.render_stateful_widget(
+            Scrollbar::new(ScrollbarOrientation::VerticalRight),
+            area.inner(Margin::new(0, 1)),
+            &mut scrollbar_state,
+        );
+    }
+}
+
+/// row under the terminal line `row` of a list drawn by `render_list` in `area`
+pub fn get_clicked_row(area: Rect, row: u16, pos: usize, len: usize) -> Option<usize> {
+    if row <= area.y || row + 1 >= area.bottom() {
+        return None;
+    }
+    let height = area.height.saturating_sub(2) as usize;
+    let clicked = get_offset(pos, len, height) + (row - area.y - 1) as usize;
+    (clicked < len).then_some(clicked)
+}
+
+/// position after PageUp / PageDown / Home / End in a list of `len` rows showing `height`,
+/// None for any other key
+pub fn get_jump(code: KeyCode, pos: usize, len: usize, height: usize) -> Option<usize> {
+    let last = len.saturating_sub(1);
+    match code {
+        KeyCode::PageUp => Some(pos.saturating_sub(height.max(1))),
+        KeyCode::PageDown => Some((pos + height.max(1)).min(last)),
+        KeyCode::Home => Some(0),
+        KeyCode::End => Some(last),
+        _ => None,
+    }
+}
+
+/// next row after `pos` whose name starts with `letter` (ignoring case), from the top when
+/// there is none below
+pub fn find_by_letter(names: &[&str], pos: usize, letter: char) -> Option<usize> {
+    if names.is_empty() {
+        return None;
+    }
+    let letter = letter.to_lowercase().next()?;
+    let starts_with = |i: &usize| {
+        names[*i]
+            .chars()
+            .next()
+            .and_then(|c| c.to_lowercase().next())
+            == Some(letter)
+    };
+    (pos + 1..names.len())
+        .chain(0..=pos.min(names.len() - 1))
+        .find(starts_with)
+}
+
+/// last component of a path or branch name: what the jump letter is matched against
+pub fn get_short_name(name: &str) -> &str {
+    let name = name.trim_end_matches('/');
+    name.rsplit('/').next().unwrap_or(name)
+}
@@ -0,0 +1,223 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Style},
+    text::{Line, Span},
+    widgets::{List, ListItem},
+    Frame,
+};
+use std::cell::Cell;
+
+use crate::{
+    git::{FileStatus, Git, Stash, StashEntry},
+    popup::Confirm,
+    tabs::{
+        mover::Move,
+        selection::{get_jump, render_list},
+    },
+};
+
+pub struct StashTab {
+    pub pos: u16,
+    pub stashes: Vec<StashEntry>,
+    /// files of the selected stash
+    pub files: Vec<(FileStatus, String)>,
+    /// stash dropped once confirmed
+    pub confirm_popup: Confirm<StashEntry>,
+    /// rows visible in the list at the last frame, for PageUp / PageDown
+    list_height: Cell<u16>,
+}
+
+impl StashTab {
+    pub fn new() -> Self {
+        StashTab {
+            pos: 0,
+            stashes: Vec::new(),
+            files: Vec::new(),
+            confirm_popup: Confirm::new(),
+            list_height: Cell::new(0),
+        }
+    }
+
+    pub fn refresh(&mut self, git: &mut Git) {
+        self.stashes = match git.get_stashes() {
+            Ok(stashes) => stashes,
+            Err(e) => {
+                git.notification
+                    .show("Stash", vec![e.message().to_string()]);
+                Vec::new()
+            }
+        };
+        if self.pos as usize >= self.stashes.len() {
+            self.pos = self.stashes.len().saturating_sub(1) as u16;
+        }
+        self.load_files(git);
+    }
+
+    fn load_files(&mut self, git: &mut Git) {
+        let Some(stash) = self.stashes.get(self.pos as usize) else {
+            self.files.clear();
+            return;
+        };
+        self.files = match git.get_stash_files(stash.id) {
+            Ok(files) => files,
+            Err(e) => {
+                git.notification
+                    .show("Stash", vec![e.message().to_string()]);
+                Vec::new()
+            }
+        };
+    }
+
+    pub fn handle_key_event(&mut self, key_event: KeyEvent, git: &mut Git) {
+        if key_event.modifiers == KeyModifiers::CONTROL {
+            self.change_block(key_event.code);
+            return;
+        }
+        let previous = self.pos;
+        match key_event.code {
+            KeyCode::Up => self.scroll_up(),
+            KeyCode::Down => self.scroll_down(),
+            KeyCode::PageUp | KeyCode::PageDown | KeyCode::Home | KeyCode::End => {
+                let height = self.list_height.get() as usize;
+                if let Some(pos) = get_jump(
+                    key_event.code,
+                    self.pos as usize,
+                    self.stashes.len(),
+                    height,
+                ) {
+                    self.pos = pos as u16;
+                }
+            }
+            _ => {}
+        }
+        if self.pos != previous {
+            self.load_files(git);
+        }
+    }
+
+    /// run the action called `name` (see `keymap::get_actions`) on the selected stash
+    pub fn run_action(&mut self, name: &str, git: &mut Git) {
+        let Some(stash) = self.stashes.get(self.pos as usize).cloned() else {
+            return;
+        };
+        match name {
+            "apply" | "pop" => {
+                let pop = name == "pop";
+                match git.apply_stash(&stash, pop) {
+                    Ok(conflicts) if conflicts.is_empty() => {}
+                    Ok(conflicts) => {
+                        let mut lines = vec![format!("stash@{{{}}} conflicts in:", stash.index)];
+                        lines.extend(conflicts.iter().map(|path| format!("  {}", path)));
+                        if pop {
+                            lines.push("the stash is kept, drop it once resolved".to_string());
+                        }
+                        git.notification.show("Stash conflicts", lines);
+                    }
+                    Err(e) => git
+                        .notification
+                        .show("Stash failed", vec![e.message().to_string()]),
+                }
+                self.refresh(git);
+            }
+            "drop" => {
+                let lines = vec![
+                    format!("Drop stash@{{{}}}: {}", stash.index, stash.message),
+                    "its changes are lost".to_string(),
+                ];
+                self.confirm_popup.ask("Drop stash", lines, stash);
+            }
+            _ => {}
+        }
+    }
+
+    pub fn confirm_key_event(&mut self, key_event: KeyEvent, git: &mut Git) {
+        if let Some(entry) = self.confirm_popup.handle_key_event(key_event) {
+            if let Err(e) = git.drop_stash(&entry) {
+                git.notification
+                    .show("Drop failed", vec![e.message().to_string()]);
+            }
+            self.refresh(git);
+        }
+    }
+
+    pub fn draw(&self, frame: &mut Frame, content: Rect, git: &Git) {
+        let [left, right] =
+            Layout::horizontal([Constraint::Percentage(40), Constraint::Fill(1)]).areas(content);
+        self.draw_stashes(frame, left);
+        self.draw_files(frame, right, git);
+        if self.confirm_popup.is_activated() {
+            self.confirm_popup.draw_popup(frame, content);
+        }
+    }
+
+    fn draw_stashes(&self, frame: &mut Frame, area: Rect) {
+        self.list_height.set(area.height.saturating_sub(2));
+        let items: Vec<ListItem> = if self.stashes.is_empty() {
+            vec![ListItem::new("No stash")]
+        } else {
+            self.stashes
+                .iter()
+                .map(|stash| ListItem::new(format!("stash@{{{}}} {}", stash.index, stash.message)))
+                .collect()
+        };
+        let list = List::new(items)
+            .block(self.make_status_block(true, "Stashes".to_string()))
+            .highlight_style(Style::default().bg(Color::DarkGray));
+        render_list(
+            frame,
+            area,
+            list,
+            self.pos as usize,
+            self.stashes.len(),
+            true,
+        );
+    }
+
+    fn draw_files(&self, frame: &mut Frame, area: Rect, git: &Git) {
+        let items: Vec<ListItem> = self
+            .files
+            .iter()
+            .map(|(status, path)| {
+                let color = git.config.colors.get_color(*status);
+                ListItem::new(Line::from(vec![
+                    Span::styled(status.to_char().to_string(), Style::default().fg(color)),
+                    Span::raw(format!(" {}", path)),
+                ]))
+            })
+            .collect();
+        let title = match self.stashes.get(self.pos as usize) {
+            Some(stash) => format!("Files of stash@{{{}}}", stash.index),
+            None => "Files".to_string(),
+        };
+        let list = List::new(items).block(self.make_status_block(false, title));
+        frame.render_widget(list, area);
+    }
+}
+
+impl Default for StashTab {
+    fn default() -> Self {
+        StashTab::new()
+    }
+}
+
+impl Move for StashTab {
+    fn scroll_up(&mut self) {
+        if self.pos > 0 {
+            self.pos -= 1;
+        }
+    }
+
+    fn scroll_down(&mut self) {
+        if (self.pos as usize) + 1 < self.stashes.len() {
+            self.pos += 1;
+        }
+    }
+
+    fn get_block_name(&self) -> Option<&'static str> {
+        Some("stashes")
+    }
+
+    /// a single block: the files only follow the selected stash
+    fn change_block(&mut self, _code: KeyCode) {}
+}
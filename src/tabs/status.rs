@@ -1,24 +1,47 @@
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use ratatui::{
-    layout::{Constraint, Layout, Rect},
+    layout::{Constraint, Layout, Position, Rect},
     style::{Color, Style, Stylize},
-    text::Text,
+    text::{Line, Span, Text},
     widgets::{List, ListItem, Paragraph},
     Frame,
 };
+use std::collections::BTreeSet;
 
 use crate::{
-    git::{get_file_diff, get_files, Git, GitFile, PushMode, TypeStaged},
-    tabs::mover::{Move, DIRECTION},
+    config::Colors,
+    fuzzy::fuzzy_match,
+    git::{
+        count_unstaged_files, get_file_diff, get_files, Discard, FileStatus, Git, GitFile,
+        HunkHeader, Ignore, IgnoreFile, ListOptions, PushMode, TypeStaged,
+    },
+    popup::{Choice, Confirm, Popup},
+    tabs::{
+        mover::{Move, DIRECTION},
+        selection::{find_by_letter, get_clicked_row, get_jump, get_short_name, render_list},
+        tree::{build_rows, get_row_key, get_row_paths, FileRow},
+    },
 };
 
-#[derive(PartialEq, Eq)]
+#[derive(Clone, Copy, PartialEq, Eq)]
 pub enum StatusBlocks {
     Unstaged,
     Staged,
     Diff,
 }
 
+pub enum DiscardAction {
+    File(String),
+    Files(Vec<String>),
+    Everything,
+    Hunk(String, HunkHeader),
+}
+
+pub enum IgnoreAction {
+    Pattern(String),
+    Append(String, IgnoreFile),
+}
+
 pub struct StatusTab {
     pub line_in_file: u16,
     pub line_in_folder_unstaged: u16,
@@ -27,6 +50,30 @@ pub struct StatusTab {
     pub nb_unstaged_file: u16,
     pub nb_staged_file: u16,
     pub filepath_diff: String,
+    /// block the file of the diff pane was selected in
+    pub diff_block: StatusBlocks,
+    /// unstaged hunks of the diff pane, empty for a staged file
+    pub hunk_headers: Vec<HunkHeader>,
+    /// file and block `hunk_headers` were read for, None to read them again
+    hunk_source: Option<(String, StatusBlocks)>,
+    pub hunk_index: usize,
+    pub confirm_popup: Confirm<DiscardAction>,
+    pub marked_unstaged: BTreeSet<String>,
+    pub marked_staged: BTreeSet<String>,
+    pub tree_mode: bool,
+    pub collapsed_unstaged: BTreeSet<String>,
+    pub collapsed_staged: BTreeSet<String>,
+    pub filter_popup: Popup,
+    pub list_options: ListOptions,
+    pub ignore_popup: Choice<IgnoreAction>,
+    pub colors: Colors,
+    /// where the blocks were drawn last, to find what the mouse clicks
+    unstaged_area: Rect,
+    staged_area: Rect,
+    diff_area: Rect,
+    /// row selected at the last frame and its path, to follow it when the list changes
+    followed_unstaged: (u16, String),
+    followed_staged: (u16, String),
 }
 
 impl StatusTab {
@@ -39,79 +86,670 @@ impl StatusTab {
             nb_unstaged_file: 0,
             nb_staged_file: 0,
             filepath_diff: String::new(),
+            diff_block: StatusBlocks::Unstaged,
+            hunk_headers: Vec::new(),
+            hunk_source: None,
+            hunk_index: 0,
+            confirm_popup: Confirm::new(),
+            marked_unstaged: BTreeSet::new(),
+            marked_staged: BTreeSet::new(),
+            tree_mode: false,
+            collapsed_unstaged: BTreeSet::new(),
+            collapsed_staged: BTreeSet::new(),
+            filter_popup: Popup::new(),
+            list_options: ListOptions::default(),
+            ignore_popup: Choice::new(),
+            colors: Colors::default(),
+            unstaged_area: Rect::default(),
+            staged_area: Rect::default(),
+            diff_area: Rect::default(),
+            followed_unstaged: (0, String::new()),
+            followed_staged: (0, String::new()),
         }
     }
     pub fn handle_key_event(&mut self, key_event: KeyEvent, git: &mut Git) {
+        if key_event.modifiers == KeyModifiers::ALT
+            && let KeyCode::Char(letter) = key_event.code
+        {
+            self.jump_to_letter(git, letter);
+            return;
+        }
         if key_event.modifiers == KeyModifiers::CONTROL {
             self.change_block(key_event.code);
             return;
         }
         match key_event.code {
+            KeyCode::Down if key_event.modifiers == KeyModifiers::SHIFT => {
+                self.mark_and_move(git, true)
+            }
+            KeyCode::Up if key_event.modifiers == KeyModifiers::SHIFT => {
+                self.mark_and_move(git, false)
+            }
             KeyCode::Down => self.scroll_down(),
             KeyCode::Up => self.scroll_up(),
-            KeyCode::Char('c') => git.commit_popup.activated = true,
-            KeyCode::Char('p') => {
+            KeyCode::PageUp | KeyCode::PageDown | KeyCode::Home | KeyCode::End => {
+                self.jump(key_event.code)
+            }
+            _ => {}
+        }
+    }
+
+    /// run the action called `name` (see `keymap::get_actions`) on the selection
+    pub fn run_action(&mut self, name: &str, git: &mut Git) {
+        match name {
+            "commit" => git.commit_popup.activated = true,
+            "push" => {
                 git.push_mode = PushMode::Push;
             }
-            KeyCode::Char('a') => {
-                if self.focused_block == StatusBlocks::Unstaged {
-                    let add = git.add(&self.filepath_diff);
-                    match add {
-                        Ok(_value) => self.handle_pos_in_blocks(StatusBlocks::Unstaged),
-                        Err(_e) => {}
-                    };
+            "mark" => self.toggle_mark(git),
+            "add" => {
+                if !self.marked_unstaged.is_empty() {
+                    let marked = std::mem::take(&mut self.marked_unstaged);
+                    self.line_in_folder_unstaged = 0;
+                    let paths = self.get_action_paths(git, marked);
+                    let errors = paths.iter().filter_map(|path| git.add(path).err());
+                    show_bulk_errors(git, "Add failed", errors.collect());
+                    return;
+                }
+                let paths = self.get_selected_paths(git);
+                let paths = self.get_action_paths(git, paths);
+                let errors: Vec<git2::Error> =
+                    paths.iter().filter_map(|path| git.add(path).err()).collect();
+                if errors.is_empty() {
+                    self.handle_pos_in_blocks(StatusBlocks::Unstaged);
+                }
+                show_bulk_errors(git, "Add failed", errors);
+            }
+            "filter" => self.filter_popup.activated = true,
+            "add_all" => {
+                self.marked_unstaged.clear();
+                self.line_in_folder_unstaged = 0;
+                if self.is_filtered() {
+                    let files = self.get_filtered_files(git, TypeStaged::Unstaged);
+                    let paths: Vec<String> =
+                        files.iter().flat_map(|file| file.get_paths()).collect();
+                    let errors = paths.iter().filter_map(|path| git.add(path).err());
+                    show_bulk_errors(git, "Add failed", errors.collect());
+                } else if let Err(e) = git.add_all() {
+                    git.notification
+                        .show("Add all failed", vec![e.message().to_string()]);
+                }
+            }
+            "restore" => {
+                if !self.marked_staged.is_empty() {
+                    let marked = std::mem::take(&mut self.marked_staged);
+                    self.line_in_folder_staged = 0;
+                    let paths = self.get_action_paths(git, marked);
+                    let errors = paths
+                        .iter()
+                        .filter_map(|path| git.restore_staged(path).err());
+                    show_bulk_errors(git, "Restore failed", errors.collect());
+                    return;
+                }
+                let paths = self.get_selected_paths(git);
+                let paths = self.get_action_paths(git, paths);
+                let errors: Vec<git2::Error> = paths
+                    .iter()
+                    .filter_map(|path| git.restore_staged(path).err())
+                    .collect();
+                if errors.is_empty() {
+                    self.handle_pos_in_blocks(StatusBlocks::Staged);
+                }
+                show_bulk_errors(git, "Restore failed", errors);
+            }
+            "restore_all" => {
+                self.marked_staged.clear();
+                self.line_in_folder_staged = 0;
+                if self.is_filtered() {
+                    let files = self.get_filtered_files(git, TypeStaged::Staged);
+                    let paths: Vec<String> =
+                        files.iter().flat_map(|file| file.get_paths()).collect();
+                    let errors = paths
+                        .iter()
+                        .filter_map(|path| git.restore_staged(path).err());
+                    show_bulk_errors(git, "Unstage failed", errors.collect());
+                } else if let Err(e) = git.restore_all_staged() {
+                    git.notification
+                        .show("Unstage all failed", vec![e.message().to_string()]);
+                }
+            }
+            "discard" => {
+                let targets: Vec<String> = if self.marked_unstaged.is_empty() {
+                    self.get_selected_paths(git)
+                } else {
+                    self.marked_unstaged.iter().cloned().collect()
+                };
+                let targets = self.get_action_paths(git, targets);
+                match targets.as_slice() {
+                    [] => {}
+                    [filepath] => {
+                        let line = match git.is_untracked(filepath) {
+                            Ok(true) => format!("Delete untracked {}", filepath),
+                            _ => format!("Restore {} from the index", filepath),
+                        };
+                        self.confirm_popup.ask(
+                            "Discard",
+                            vec![line, "This cannot be undone".to_string()],
+                            DiscardAction::File(filepath.clone()),
+                        );
+                    }
+                    _ => {
+                        let mut lines =
+                            vec![format!("Discard the changes of {} file(s)", targets.len())];
+                        lines.extend(targets.iter().take(10).map(|path| format!("  {}", path)));
+                        lines.push("Untracked files are deleted, this cannot be undone".to_string());
+                        self.confirm_popup
+                            .ask("Discard", lines, DiscardAction::Files(targets));
+                    }
+                }
+            }
+            "discard_all" => {
+                let nb_files = count_unstaged_files(&git.repo).unwrap_or(0);
+                if nb_files == 0 {
+                    return;
                 }
+                self.confirm_popup.ask_typed(
+                    "Discard everything",
+                    vec![
+                        format!("Discard the changes of {} file(s)", nb_files),
+                        "Untracked files are deleted, this cannot be undone".to_string(),
+                    ],
+                    "discard",
+                    DiscardAction::Everything,
+                );
             }
-            KeyCode::Char('r') => {
-                if self.focused_block == StatusBlocks::Staged {
-                    let restore = git.restore_staged(&self.filepath_diff);
-                    match restore {
-                        Ok(_value) => self.handle_pos_in_blocks(StatusBlocks::Staged),
-                        Err(_e) => {}
-                    };
+            "ignore" => {
+                if let Some(filepath) = self.get_selected_paths(git).first() {
+                    self.ask_ignore_pattern(filepath);
+                }
+            }
+            "show_ignored" => {
+                self.list_options.show_ignored = !self.list_options.show_ignored;
+                self.line_in_folder_unstaged = 0;
+                git.refresh_status_details(self.list_options);
+            }
+            "expand_untracked" => {
+                self.list_options.recurse_untracked = !self.list_options.recurse_untracked;
+                self.line_in_folder_unstaged = 0;
+                git.refresh_status_details(self.list_options);
+            }
+            "tree" => {
+                self.tree_mode = !self.tree_mode;
+                self.line_in_folder_unstaged = 0;
+                self.line_in_folder_staged = 0;
+            }
+            "collapse" => self.toggle_collapse(git),
+            "next_hunk" if self.hunk_index + 1 < self.hunk_headers.len() => {
+                self.hunk_index += 1;
+            }
+            "previous_hunk" => {
+                self.hunk_index = self.hunk_index.saturating_sub(1);
+            }
+            "discard_hunk" => {
+                if self.diff_block != StatusBlocks::Unstaged {
+                    git.notification.show(
+                        "Discard hunk",
+                        vec!["Only the hunks of an unstaged file can be discarded".to_string()],
+                    );
+                    return;
+                }
+                match self.hunk_headers.get(self.hunk_index) {
+                    Some(header) => self.confirm_popup.ask(
+                        "Discard hunk",
+                        vec![
+                            format!("Discard hunk {} of {}", header, self.filepath_diff),
+                            "This cannot be undone".to_string(),
+                        ],
+                        DiscardAction::Hunk(self.filepath_diff.clone(), header.clone()),
+                    ),
+                    None => git
+                        .notification
+                        .show("Discard hunk", vec!["No unstaged hunk".to_string()]),
                 }
             }
             _ => {}
         }
     }
 
+    pub fn confirm_key_event(&mut self, key_event: KeyEvent, git: &mut Git) {
+        let result = match self.confirm_popup.handle_key_event(key_event) {
+            Some(DiscardAction::File(filepath)) => {
+                let result = git.discard_file(&filepath);
+                if result.is_ok() {
+                    self.handle_pos_in_blocks(StatusBlocks::Unstaged);
+                }
+                result
+            }
+            Some(DiscardAction::Files(filepaths)) => {
+                self.marked_unstaged.clear();
+                self.line_in_folder_unstaged = 0;
+                let errors = filepaths
+                    .iter()
+                    .filter_map(|path| git.discard_file(path).err());
+                show_bulk_errors(git, "Discard failed", errors.collect());
+                return;
+            }
+            Some(DiscardAction::Everything) => {
+                self.marked_unstaged.clear();
+                self.line_in_folder_unstaged = 0;
+                git.discard_all()
+            }
+            Some(DiscardAction::Hunk(filepath, header)) => {
+                self.hunk_index = 0;
+                self.hunk_source = None;
+                git.discard_hunk(&filepath, &header)
+            }
+            None => return,
+        };
+        if let Err(e) = result {
+            git.notification
+                .show("Discard failed", vec![e.message().to_string()]);
+        }
+    }
+
+    /// first step of `i`: which pattern ignores the file
+    fn ask_ignore_pattern(&mut self, filepath: &str) {
+        let path = filepath.trim_end_matches('/');
+        let kind = if filepath.ends_with('/') { "directory" } else { "file" };
+        let mut options = vec![(
+            'f',
+            format!("this {}: /{}", kind, filepath),
+            IgnoreAction::Pattern(format!("/{}", filepath)),
+        )];
+        if let Some((dir, _)) = path.rsplit_once('/') {
+            options.push((
+                'd',
+                format!("its directory: /{}/", dir),
+                IgnoreAction::Pattern(format!("/{}/", dir)),
+            ));
+        }
+        let name = path.rsplit('/').next().unwrap_or(path);
+        if !filepath.ends_with('/')
+            && let Some((stem, extension)) = name.rsplit_once('.')
+            && !stem.is_empty()
+        {
+            options.push((
+                'e',
+                format!("its extension: *.{}", extension),
+                IgnoreAction::Pattern(format!("*.{}", extension)),
+            ));
+        }
+        self.ignore_popup
+            .ask("Ignore", vec![format!("Ignore {}", filepath)], options);
+    }
+
+    pub fn ignore_key_event(&mut self, key_event: KeyEvent, git: &mut Git) {
+        match self.ignore_popup.handle_key_event(key_event) {
+            Some(IgnoreAction::Pattern(pattern)) => {
+                let options = [
+                    ('g', IgnoreFile::GitIgnore),
+                    ('x', IgnoreFile::InfoExclude),
+                    ('G', IgnoreFile::Global),
+                ]
+                .into_iter()
+                .map(|(key, target)| {
+                    let action = IgnoreAction::Append(pattern.clone(), target);
+                    (key, target.get_name().to_string(), action)
+                })
+                .collect();
+                self.ignore_popup
+                    .ask("Ignore", vec![format!("Add {} to", pattern)], options);
+            }
+            Some(IgnoreAction::Append(pattern, target)) => {
+                match git.append_ignore(&pattern, target) {
+                    Ok(()) => self.handle_pos_in_blocks(StatusBlocks::Unstaged),
+                    Err(e) => git
+                        .notification
+                        .show("Ignore failed", vec![e.message().to_string()]),
+                }
+            }
+            None => {}
+        }
+    }
+
+    /// keys typed while the `/` filter line is being edited. The selected file stays
+    /// selected while it matches, see `follow_selection`
+    pub fn filter_key_event(&mut self, key_event: KeyEvent) {
+        match key_event.code {
+            KeyCode::Enter => self.filter_popup.activated = false,
+            KeyCode::Esc => {
+                self.filter_popup.activated = false;
+                self.filter_popup.set_input("");
+            }
+            KeyCode::Char(to_insert) => {
+                self.filter_popup.enter_char(to_insert);
+            }
+            KeyCode::Backspace => {
+                self.filter_popup.delete_char();
+            }
+            KeyCode::Left => self.filter_popup.move_cursor_left(),
+            KeyCode::Right => self.filter_popup.move_cursor_right(),
+            _ => {}
+        }
+    }
+
+    fn is_filtered(&self) -> bool {
+        !self.filter_popup.input.is_empty()
+    }
+
+    /// files of one block, keeping only the paths matching the `/` filter
+    fn get_filtered_files(&self, git: &Git, typeneeded: TypeStaged) -> Vec<GitFile> {
+        let files = get_files(git, typeneeded);
+        if !self.is_filtered() {
+            return files;
+        }
+        files
+            .into_iter()
+            .filter(|file| fuzzy_match(&self.filter_popup.input, &file.filename).is_some())
+            .collect()
+    }
+
+    fn get_focused_rows(&self, git: &Git) -> (Vec<GitFile>, Vec<FileRow>) {
+        let (typeneeded, collapsed) = match self.focused_block {
+            StatusBlocks::Staged => (TypeStaged::Staged, &self.collapsed_staged),
+            _ => (TypeStaged::Unstaged, &self.collapsed_unstaged),
+        };
+        let files = self.get_filtered_files(git, typeneeded);
+        let rows = build_rows(&files, self.tree_mode, collapsed);
+        (files, rows)
+    }
+
+    fn get_focused_pos(&self) -> u16 {
+        match self.focused_block {
+            StatusBlocks::Staged => self.line_in_folder_staged,
+            _ => self.line_in_folder_unstaged,
+        }
+    }
+
+    /// files behind the selected row, several when a directory is selected
+    fn get_selected_paths(&self, git: &Git) -> Vec<String> {
+        let (files, rows) = self.get_focused_rows(git);
+        match rows.get(self.get_focused_pos() as usize) {
+            Some(row) => get_row_paths(&files, row),
+            None => Vec::new(),
+        }
+    }
+
+    /// paths to hand to git for the selected files: renames bring their old path along
+    fn get_action_paths(&self, git: &Git, filenames: impl IntoIterator<Item = String>) -> Vec<String> {
+        let (files, _rows) = self.get_focused_rows(git);
+        let mut paths = Vec::new();
+        for filename in filenames {
+            match files.iter().find(|file| file.filename == filename) {
+                Some(file) => paths.extend(file.get_paths()),
+                None => paths.push(filename),
+            }
+        }
+        paths
+    }
+
+    fn get_focused_marks(&mut self) -> Option<&mut BTreeSet<String>> {
+        match self.focused_block {
+            StatusBlocks::Unstaged => Some(&mut self.marked_unstaged),
+            StatusBlocks::Staged => Some(&mut self.marked_staged),
+            StatusBlocks::Diff => None,
+        }
+    }
+
+    fn toggle_mark(&mut self, git: &Git) {
+        let paths = self.get_selected_paths(git);
+        let Some(marks) = self.get_focused_marks() else {
+            return;
+        };
+        if paths.iter().all(|path| marks.contains(path)) {
+            paths.iter().for_each(|path| {
+                marks.remove(path);
+            });
+        } else {
+            marks.extend(paths);
+        }
+    }
+
+    /// range selection: mark the current row, move, then mark the new one
+    fn mark_and_move(&mut self, git: &Git, down: bool) {
+        let paths = self.get_selected_paths(git);
+        if let Some(marks) = self.get_focused_marks() {
+            marks.extend(paths);
+        }
+        if down {
+            self.scroll_down();
+        } else {
+            self.scroll_up();
+        }
+        let paths = self.get_selected_paths(git);
+        if let Some(marks) = self.get_focused_marks() {
+            marks.extend(paths);
+        }
+    }
+
+    fn toggle_collapse(&mut self, git: &Git) {
+        let (_files, rows) = self.get_focused_rows(git);
+        let Some(FileRow::Dir { path, .. }) = rows.get(self.get_focused_pos() as usize) else {
+            return;
+        };
+        let collapsed = match self.focused_block {
+            StatusBlocks::Staged => &mut self.collapsed_staged,
+            _ => &mut self.collapsed_unstaged,
+        };
+        if !collapsed.remove(path) {
+            collapsed.insert(path.clone());
+        }
+    }
+
     pub fn draw(&mut self, frame: &mut Frame, content: Rect, git: &Git) {
+        self.colors = git.config.colors;
+        if git.repo.is_bare() {
+            let message =
+                Paragraph::new("Bare repository: no working tree, see the Branch and Log pages")
+                    .centered()
+                    .block(self.make_status_block(true, "Status".to_string()));
+            frame.render_widget(message, content);
+            return;
+        }
         let [left, right] = Layout::horizontal([Constraint::Fill(1); 2]).areas(content);
+        let left = if self.is_filtered() || self.filter_popup.activated {
+            let [filter_line, lists] =
+                Layout::vertical([Constraint::Length(1), Constraint::Fill(1)]).areas(left);
+            self.draw_filter(frame, filter_line);
+            lists
+        } else {
+            left
+        };
         let [top_left, bottom_left] = Layout::vertical([Constraint::Fill(1); 2]).areas(left);
 
-        let staged_files: Vec<GitFile> =
-            get_files(git, TypeStaged::Staged).expect("Error on staged file");
-        let unstaged_files: Vec<GitFile> =
-            get_files(git, TypeStaged::Unstaged).expect("Error on unstaged file");
+        let staged_files = self.get_filtered_files(git, TypeStaged::Staged);
+        let unstaged_files = self.get_filtered_files(git, TypeStaged::Unstaged);
+        let unstaged_rows = build_rows(&unstaged_files, self.tree_mode, &self.collapsed_unstaged);
+        let staged_rows = build_rows(&staged_files, self.tree_mode, &self.collapsed_staged);
+        // before clamping, a shorter list must not stop the selection from following its path
+        follow_selection(
+            &mut self.line_in_folder_unstaged,
+            &mut self.followed_unstaged,
+            &unstaged_files,
+            &unstaged_rows,
+        );
+        follow_selection(
+            &mut self.line_in_folder_staged,
+            &mut self.followed_staged,
+            &staged_files,
+            &staged_rows,
+        );
+        // the lists may have shrunk since the last frame and the followed path gone
+        self.line_in_folder_unstaged = self
+            .line_in_folder_unstaged
+            .min(unstaged_rows.len().saturating_sub(1) as u16);
+        self.line_in_folder_staged = self
+            .line_in_folder_staged
+            .min(staged_rows.len().saturating_sub(1) as u16);
         let file_to_read = match self.focused_block {
             StatusBlocks::Diff => self.filepath_diff.clone(),
             StatusBlocks::Staged => {
-                self.get_selected_file_path(staged_files.clone(), self.line_in_folder_staged)
-            }
-            StatusBlocks::Unstaged => {
-                self.get_selected_file_path(unstaged_files.clone(), self.line_in_folder_unstaged)
+                self.get_selected_file_path(&staged_files, &staged_rows, self.line_in_folder_staged)
             }
+            StatusBlocks::Unstaged => self.get_selected_file_path(
+                &unstaged_files,
+                &unstaged_rows,
+                self.line_in_folder_unstaged,
+            ),
         };
 
-        self.nb_unstaged_file = unstaged_files.len() as u16;
-        self.nb_staged_file = staged_files.len() as u16;
+        // rows, not files: directories count as a line in tree mode
+        self.nb_unstaged_file = unstaged_rows.len() as u16;
+        self.nb_staged_file = staged_rows.len() as u16;
+
+        // forget marks of files that left the list
+        self.marked_unstaged
+            .retain(|path| unstaged_files.iter().any(|file| &file.filename == path));
+        self.marked_staged
+            .retain(|path| staged_files.iter().any(|file| &file.filename == path));
+
+        self.load_hunk_headers(git, &file_to_read);
 
-        self.draw_diff(frame, right, file_to_read);
-        self.draw_unstaged(frame, top_left, unstaged_files);
-        self.draw_staged(frame, bottom_left, staged_files);
+        self.unstaged_area = top_left;
+        self.staged_area = bottom_left;
+        self.diff_area = right;
+        self.draw_diff(frame, right, git, file_to_read);
+        self.draw_unstaged(frame, top_left, &unstaged_files, &unstaged_rows);
+        self.draw_staged(frame, bottom_left, &staged_files, &staged_rows);
+        if self.confirm_popup.is_activated() {
+            self.confirm_popup.draw_popup(frame, content);
+        }
+        if self.ignore_popup.is_activated() {
+            self.ignore_popup.draw_popup(frame, content);
+        }
+    }
+
+    /// read the hunks of the diff pane when another file is selected or the diff is focused
+    fn load_hunk_headers(&mut self, git: &Git, filepath: &str) {
+        let source = (filepath.to_string(), self.diff_block);
+        if self.hunk_source.as_ref() == Some(&source) {
+            return;
+        }
+        // staged hunks are not discarded: only the index -> working tree diff is read
+        self.hunk_headers = match self.diff_block {
+            StatusBlocks::Unstaged => git.get_hunk_headers(filepath).unwrap_or_default(),
+            _ => Vec::new(),
+        };
+        if self.hunk_index >= self.hunk_headers.len() {
+            self.hunk_index = 0;
+        }
+        self.hunk_source = Some(source);
+    }
+
+    /// the working tree may have changed while the list was focused
+    fn focus_diff(&mut self) {
+        self.focused_block = StatusBlocks::Diff;
+        self.hunk_source = None;
     }
 
-    fn get_selected_file_path(&mut self, files: Vec<GitFile>, pos: u16) -> String {
-        if !files.is_empty() {
-            self.filepath_diff = files[pos as usize].filename.clone();
-            files[pos as usize].filename.clone()
+    /// focus the block under the mouse and select the row clicked.
+    /// True when a file (or directory) was clicked
+    pub fn click(&mut self, column: u16, row: u16) -> bool {
+        let position = Position::new(column, row);
+        if self.diff_area.contains(position) {
+            self.focus_diff();
+            return false;
+        }
+        let (block, area) = if self.unstaged_area.contains(position) {
+            (StatusBlocks::Unstaged, self.unstaged_area)
+        } else if self.staged_area.contains(position) {
+            (StatusBlocks::Staged, self.staged_area)
         } else {
-            String::new()
+            return false;
+        };
+        self.focused_block = block;
+        let (line, nb_rows) = match self.focused_block {
+            StatusBlocks::Staged => (&mut self.line_in_folder_staged, self.nb_staged_file),
+            _ => (&mut self.line_in_folder_unstaged, self.nb_unstaged_file),
+        };
+        let Some(clicked) = get_clicked_row(area, row, *line as usize, nb_rows as usize) else {
+            return false;
+        };
+        *line = clicked as u16;
+        self.line_in_file = 0;
+        true
+    }
+
+    /// PageUp / PageDown / Home / End in the focused block
+    fn jump(&mut self, code: KeyCode) {
+        if self.focused_block == StatusBlocks::Diff {
+            let height = self.diff_area.height.saturating_sub(2);
+            self.line_in_file = match code {
+                KeyCode::PageUp => self.line_in_file.saturating_sub(height),
+                KeyCode::PageDown => self.line_in_file + height,
+                KeyCode::Home => 0,
+                _ => self.line_in_file,
+            };
+            return;
+        }
+        let (line, nb_rows, area) = match self.focused_block {
+            StatusBlocks::Staged => (
+                &mut self.line_in_folder_staged,
+                self.nb_staged_file,
+                self.staged_area,
+            ),
+            _ => (
+                &mut self.line_in_folder_unstaged,
+                self.nb_unstaged_file,
+                self.unstaged_area,
+            ),
+        };
+        let height = area.height.saturating_sub(2) as usize;
+        if let Some(pos) = get_jump(code, *line as usize, nb_rows as usize, height) {
+            *line = pos as u16;
+            self.line_in_file = 0;
+        }
+    }
+
+    /// select the next file or directory of the focused list starting with `letter`
+    fn jump_to_letter(&mut self, git: &Git, letter: char) {
+        if self.focused_block == StatusBlocks::Diff {
+            return;
+        }
+        let (files, rows) = self.get_focused_rows(git);
+        let keys: Vec<String> = rows.iter().map(|row| get_row_key(&files, row)).collect();
+        let names: Vec<&str> = keys.iter().map(|key| get_short_name(key)).collect();
+        let Some(pos) = find_by_letter(&names, self.get_focused_pos() as usize, letter) else {
+            return;
+        };
+        match self.focused_block {
+            StatusBlocks::Staged => self.line_in_folder_staged = pos as u16,
+            _ => self.line_in_folder_unstaged = pos as u16,
+        }
+        self.line_in_file = 0;
+    }
+
+    fn draw_filter(&self, frame: &mut Frame, pos: Rect) {
+        let style = if self.filter_popup.activated {
+            Style::default().fg(Color::Yellow)
+        } else {
+            Style::default().fg(Color::DarkGray)
+        };
+        let line = Line::from(vec![
+            Span::styled("/", style),
+            Span::raw(self.filter_popup.input.clone()),
+        ]);
+        frame.render_widget(Paragraph::new(line), pos);
+        if self.filter_popup.activated {
+            frame
+                .set_cursor_position((pos.x + 1 + self.filter_popup.character_index as u16, pos.y));
         }
     }
 
-    fn draw_diff(&self, frame: &mut Frame, pos: Rect, file: String) {
-        let text = match get_file_diff(&file) {
+    fn get_selected_file_path(&mut self, files: &[GitFile], rows: &[FileRow], pos: u16) -> String {
+        let filepath = match rows.get(pos as usize) {
+            Some(FileRow::File { index, .. }) => files[*index].filename.clone(),
+            Some(FileRow::Dir { path, .. }) => path.clone(),
+            None => return String::new(),
+        };
+        self.filepath_diff = filepath.clone();
+        self.diff_block = self.focused_block;
+        filepath
+    }
+
+    fn draw_diff(&self, frame: &mut Frame, pos: Rect, git: &Git, file: String) {
+        let workdir = git.repo.workdir().unwrap_or(git.repo.path());
+        let text = match get_file_diff(workdir, &file, &git.config.diff) {
             // Retourne Text avec styles
             Ok(styled_text) => styled_text,
             Err(e) => Text::from(e),
@@ -122,79 +760,209 @@ impl StatusTab {
                 .style(Style::default())
                 .block(self.make_status_block(
                     self.focused_block == StatusBlocks::Diff,
-                    "Diff".to_string(),
+                    self.get_diff_title(),
                 ))
                 .scroll((self.line_in_file, 0));
 
         frame.render_widget(diff, pos);
     }
 
-    fn draw_unstaged(&self, frame: &mut Frame, pos: Rect, files: Vec<GitFile>) {
-        let mut items: Vec<ListItem> = if files.is_empty() {
-            vec![ListItem::new("No unstaged changes")]
+    fn get_diff_title(&self) -> String {
+        match self.hunk_headers.get(self.hunk_index) {
+            Some(header) if self.focused_block == StatusBlocks::Diff => format!(
+                "Diff (hunk {}/{}: {})",
+                self.hunk_index + 1,
+                self.hunk_headers.len(),
+                header
+            ),
+            _ => "Diff".to_string(),
+        }
+    }
+
+    fn get_status_style(&self, status: FileStatus) -> Style {
+        let style = Style::default().fg(self.colors.get_color(status));
+        if status == FileStatus::Conflicted {
+            style.bold()
         } else {
-            files
-                .iter()
-                .skip(self.line_in_folder_unstaged.into())
-                .map(|file| {
-                    let style = match file.status {
-                        'm' => Style::default().fg(Color::Yellow), // Modified
-                        'd' => Style::default().fg(Color::Red),    // Deleted
-                        'r' => Style::default().fg(Color::Blue),   // Untracked
-                        'n' => Style::default().fg(Color::Green),  // Added
-                        _ => Style::default(),
-                    };
-
-                    ListItem::new(format!("{} {}", file.status, file.filename)).style(style)
-                })
-                .collect()
+            style
+        }
+    }
+
+    fn make_row_item(
+        &self,
+        files: &[GitFile],
+        row: &FileRow,
+        marks: &BTreeSet<String>,
+    ) -> ListItem<'static> {
+        let (spans, style, marked) = match row {
+            FileRow::Dir {
+                path,
+                depth,
+                nb_files,
+                collapsed,
+            } => {
+                let name = path.trim_end_matches('/').rsplit('/').next().unwrap_or("");
+                let arrow = if *collapsed { '▸' } else { '▾' };
+                let text = format!("{}{} {}/ ({})", "  ".repeat(*depth), arrow, name, nb_files);
+                let marked = get_row_paths(files, row)
+                    .iter()
+                    .all(|path| marks.contains(path));
+                (
+                    vec![Span::raw(text)],
+                    Style::default().fg(Color::Cyan),
+                    marked,
+                )
+            }
+            FileRow::File { index, depth } => {
+                let file = &files[*index];
+                let name = if self.tree_mode {
+                    let path = file.filename.trim_end_matches('/');
+                    let name = path.rsplit('/').next().unwrap_or(path);
+                    if file.filename.ends_with('/') {
+                        format!("{}/", name)
+                    } else {
+                        name.to_string()
+                    }
+                } else {
+                    file.filename.clone()
+                };
+                let mut spans = vec![Span::raw(format!(
+                    "{}{} ",
+                    "  ".repeat(*depth),
+                    file.status.to_char()
+                ))];
+                spans.extend(self.highlight_matches(file, &name));
+                let style = self.get_status_style(file.status);
+                (spans, style, marks.contains(&file.filename))
+            }
+        };
+
+        let prefix = if marked {
+            "* "
+        } else if !marks.is_empty() {
+            "  "
+        } else {
+            ""
         };
-        if self.focused_block == StatusBlocks::Unstaged {
-            items[0] = items[0].clone().on_dark_gray();
+        let mut line = Line::from(spans);
+        if !prefix.is_empty() {
+            line.spans.insert(0, Span::raw(prefix));
+        }
+        let style = if marked { style.bold() } else { style };
+        ListItem::new(line).style(style)
+    }
+
+    /// display name of a file split in spans, the chars matched by the `/` filter underlined
+    fn highlight_matches(&self, file: &GitFile, name: &str) -> Vec<Span<'static>> {
+        let display = file.get_display_name(name);
+        let Some(indices) =
+            fuzzy_match(&self.filter_popup.input, &file.filename).filter(|_| self.is_filtered())
+        else {
+            return vec![Span::raw(display)];
+        };
+        // matches are indices in the full path: shift them onto the name shown,
+        // which may be a basename (tree mode) following the old name of a rename
+        let hidden = file.filename.chars().count() - name.chars().count();
+        let name_start = match &file.old_filename {
+            Some(old) => old.chars().count() + " → ".chars().count(),
+            None => 0,
+        };
+        let highlighted: BTreeSet<usize> = indices
+            .into_iter()
+            .filter(|i| *i >= hidden)
+            .map(|i| i - hidden + name_start)
+            .collect();
+
+        let match_style = Style::default().underlined().bold();
+        let mut spans = Vec::new();
+        let mut current = String::new();
+        let mut current_matched = false;
+        for (i, c) in display.chars().enumerate() {
+            let matched = highlighted.contains(&i);
+            if matched != current_matched && !current.is_empty() {
+                let text = std::mem::take(&mut current);
+                spans.push(if current_matched {
+                    Span::styled(text, match_style)
+                } else {
+                    Span::raw(text)
+                });
+            }
+            current_matched = matched;
+            current.push(c);
+        }
+        spans.push(if current_matched {
+            Span::styled(current, match_style)
+        } else {
+            Span::raw(current)
+        });
+        spans
+    }
+
+    fn get_list_title(&self, name: &str, nb_files: usize, nb_marked: usize) -> String {
+        if nb_marked == 0 {
+            format!("{} ({})", name, nb_files)
+        } else {
+            format!("{} ({}, {} marked)", name, nb_files, nb_marked)
         }
+    }
+
+    fn get_empty_text(&self, text: &'static str) -> &'static str {
+        if self.is_filtered() {
+            "No file matching the filter"
+        } else {
+            text
+        }
+    }
+
+    fn draw_unstaged(&self, frame: &mut Frame, pos: Rect, files: &[GitFile], rows: &[FileRow]) {
+        let items: Vec<ListItem> = if files.is_empty() {
+            vec![ListItem::new(self.get_empty_text("No unstaged changes"))]
+        } else {
+            rows.iter()
+                .map(|row| self.make_row_item(files, row, &self.marked_unstaged))
+                .collect()
+        };
         let unstaged_list = List::new(items)
             .block(self.make_status_block(
                 self.focused_block == StatusBlocks::Unstaged,
-                format!("Unstaged ({})", files.len()),
+                self.get_list_title("Unstaged", files.len(), self.marked_unstaged.len()),
             ))
             .highlight_style(Style::default().bg(Color::DarkGray));
 
-        frame.render_widget(unstaged_list, pos);
+        render_list(
+            frame,
+            pos,
+            unstaged_list,
+            self.line_in_folder_unstaged.into(),
+            rows.len(),
+            self.focused_block == StatusBlocks::Unstaged,
+        );
     }
 
-    fn draw_staged(&self, frame: &mut Frame, pos: Rect, files: Vec<GitFile>) {
-        let mut items: Vec<ListItem> = if files.is_empty() {
-            vec![ListItem::new("No staged changes")]
+    fn draw_staged(&self, frame: &mut Frame, pos: Rect, files: &[GitFile], rows: &[FileRow]) {
+        let items: Vec<ListItem> = if files.is_empty() {
+            vec![ListItem::new(self.get_empty_text("No staged changes"))]
         } else {
-            files
-                .iter()
-                .skip(self.line_in_folder_staged.into())
-                .map(|file| {
-                    let style = match file.status {
-                        'm' => Style::default().fg(Color::Yellow), // Modified
-                        'd' => Style::default().fg(Color::Red),    // Deleted
-                        'r' => Style::default().fg(Color::Blue),   // Untracked
-                        'n' => Style::default().fg(Color::Green),  // Added
-                        _ => Style::default(),
-                    };
-
-                    ListItem::new(format!("{} {}", file.status, file.filename)).style(style)
-                })
+            rows.iter()
+                .map(|row| self.make_row_item(files, row, &self.marked_staged))
                 .collect()
         };
 
-        if self.focused_block == StatusBlocks::Staged {
-            items[0] = items[0].clone().on_dark_gray();
-        }
-
         let staged_list = List::new(items)
             .block(self.make_status_block(
                 self.focused_block == StatusBlocks::Staged,
-                format!("Staged ({})", files.len()),
+                self.get_list_title("Staged", files.len(), self.marked_staged.len()),
             ))
             .highlight_style(Style::default().bg(Color::DarkGray));
 
-        frame.render_widget(staged_list, pos);
+        render_list(
+            frame,
+            pos,
+            staged_list,
+            self.line_in_folder_staged.into(),
+            rows.len(),
+            self.focused_block == StatusBlocks::Staged,
+        );
     }
 
     pub fn handle_pos_in_blocks(&mut self, block: StatusBlocks) {
@@ -207,6 +975,31 @@ impl StatusTab {
     }
 }
 
+/// keep the selection on the same path when the list changes under it (files appearing,
+/// filter, tree mode), unless the selection itself moved since the last frame
+fn follow_selection(
+    line: &mut u16,
+    followed: &mut (u16, String),
+    files: &[GitFile],
+    rows: &[FileRow],
+) {
+    let keys: Vec<String> = rows.iter().map(|row| get_row_key(files, row)).collect();
+    if *line == followed.0
+        && keys.get(*line as usize) != Some(&followed.1)
+        && let Some(pos) = keys.iter().position(|key| *key == followed.1)
+    {
+        *line = pos as u16;
+    }
+    *followed = (*line, keys.get(*line as usize).cloned().unwrap_or_default());
+}
+
+fn show_bulk_errors(git: &mut Git, title: &str, errors: Vec<git2::Error>) {
+    if !errors.is_empty() {
+        let lines = errors.iter().map(|e| e.message().to_string()).collect();
+        git.notification.show(title, lines);
+    }
+}
+
 impl Move for StatusTab {
     fn scroll_down(&mut self) {
         match self.focused_block {
@@ -245,12 +1038,32 @@ impl Move for StatusTab {
             }
         }
     }
+    fn get_block_name(&self) -> Option<&'static str> {
+        Some(match self.focused_block {
+            StatusBlocks::Unstaged => "unstaged",
+            StatusBlocks::Staged => "staged",
+            StatusBlocks::Diff => "diff",
+        })
+    }
+
+    fn focus_block(&mut self, name: &str) {
+        self.focused_block = match name {
+            "unstaged" => StatusBlocks::Unstaged,
+            "staged" => StatusBlocks::Staged,
+            "diff" => {
+                self.focus_diff();
+                return;
+            }
+            _ => return,
+        };
+    }
+
     fn change_block(&mut self, code: KeyCode) {
         if !DIRECTION.contains(&code) {
             return;
         }
         if self.focused_block != StatusBlocks::Diff && code == KeyCode::Right {
-            self.focused_block = StatusBlocks::Diff;
+            self.focus_diff();
             return;
         }
         if self.focused_block == StatusBlocks::Diff && code == KeyCode::Left {
@@ -0,0 +1,103 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::git::GitFile;
+
+/// a line of the Unstaged / Staged lists: a directory (tree mode only) or a file
+pub enum FileRow {
+    Dir {
+        path: String,
+        depth: usize,
+        nb_files: usize,
+        collapsed: bool,
+    },
+    File {
+        index: usize,
+        depth: usize,
+    },
+}
+
+#[derive(Default)]
+struct Node {
+    dirs: BTreeMap<String, Node>,
+    files: Vec<usize>,
+}
+
+impl Node {
+    fn count_files(&self) -> usize {
+        self.files.len() + self.dirs.values().map(Node::count_files).sum::<usize>()
+    }
+}
+
+/// rows to display for `files`: one row per file in flat mode, grouped by directory in tree mode
+pub fn build_rows(files: &[GitFile], tree_mode: bool, collapsed: &BTreeSet<String>) -> Vec<FileRow> {
+    if !tree_mode {
+        return (0..files.len())
+            .map(|index| FileRow::File { index, depth: 0 })
+            .collect();
+    }
+
+    let mut root = Node::default();
+    for (index, file) in files.iter().enumerate() {
+        // untracked directories are listed as "dir/": keep them as a leaf
+        let path = file.filename.trim_end_matches('/');
+        let mut node = &mut root;
+        if let Some((dirs, _name)) = path.rsplit_once('/') {
+            for dir in dirs.split('/') {
+                node = node.dirs.entry(dir.to_string()).or_default();
+            }
+        }
+        node.files.push(index);
+    }
+
+    let mut rows = Vec::new();
+    flatten(&root, "", 0, collapsed, &mut rows);
+    rows
+}
+
+fn flatten(
+    node: &Node,
+    parent: &str,
+    depth: usize,
+    collapsed: &BTreeSet<String>,
+    rows: &mut Vec<FileRow>,
+) {
+    for (name, child) in &node.dirs {
+        let path = format!("{}{}/", parent, name);
+        let is_collapsed = collapsed.contains(&path);
+        rows.push(FileRow::Dir {
+            path: path.clone(),
+            depth,
+            nb_files: child.count_files(),
+            collapsed: is_collapsed,
+        });
+        if !is_collapsed {
+            flatten(child, &path, depth + 1, collapsed, rows);
+        }
+    }
+    for index in &node.files {
+        rows.push(FileRow::File {
+            index: *index,
+            depth,
+        });
+    }
+}
+
+/// path identifying a row: the file path, or the directory one ending with '/'
+pub fn get_row_key(files: &[GitFile], row: &FileRow) -> String {
+    match row {
+        FileRow::File { index, .. } => files[*index].filename.clone(),
+        FileRow::Dir { path, .. } => path.clone(),
+    }
+}
+
+/// paths of the files behind a row: the file itself or every file under the directory
+pub fn get_row_paths(files: &[GitFile], row: &FileRow) -> Vec<String> {
+    match row {
+        FileRow::File { index, .. } => vec![files[*index].filename.clone()],
+        FileRow::Dir { path, .. } => files
+            .iter()
+            .filter(|file| file.filename.starts_with(path.as_str()))
+            .map(|file| file.filename.clone())
+            .collect(),
+    }
+}